@@ -0,0 +1,43 @@
+//! Per-query cost of `is_business_day` over every day of a year, compared with doing the
+//! holiday set lookup before the weekend check.
+//! run with `cargo bench --bench business_day`
+use std::hint::black_box;
+use std::time::Instant;
+use usec::calendar::{Calendar, UsExchangeCalendar};
+
+const ITERATIONS: u32 = 1_000;
+
+fn main() {
+    let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+    let days: Vec<_> = Calendar::from_ymd(2022, 1, 1)
+        .iter_days()
+        .take(365)
+        .collect();
+    let queries = ITERATIONS * days.len() as u32;
+
+    let now = Instant::now();
+    for _ in 0..ITERATIONS {
+        for day in &days {
+            black_box(cal.is_business_day(black_box(*day)));
+        }
+    }
+    let weekend_first = now.elapsed();
+
+    let now = Instant::now();
+    for _ in 0..ITERATIONS {
+        for day in &days {
+            let day = black_box(*day);
+            black_box(!cal.is_holiday(day) && !cal.is_weekend(day));
+        }
+    }
+    let holiday_first = now.elapsed();
+
+    println!(
+        "weekend mask first: {:?} per query",
+        weekend_first / queries
+    );
+    println!(
+        "holiday set first:  {:?} per query",
+        holiday_first / queries
+    );
+}
@@ -0,0 +1,26 @@
+//! Repeated business day counts from a fixed inception date, with and without the prefix-sum
+//! index, run with `cargo bench --bench count_index`
+use std::hint::black_box;
+use std::time::Instant;
+use usec::calendar::{Bounds, Calendar, UsExchangeCalendar};
+
+fn main() {
+    let mut cal = UsExchangeCalendar::with_default_range(true).get_cal();
+    let inception = Calendar::from_ymd(2000, 1, 3);
+    let days: Vec<_> = inception.iter_days().take(365 * 20).collect();
+
+    let count_all = |cal: &Calendar| {
+        let now = Instant::now();
+        for day in &days {
+            black_box(cal.business_days_between(inception, black_box(*day), Bounds::Inclusive));
+        }
+        now.elapsed() / days.len() as u32
+    };
+
+    let scan = count_all(&cal);
+    cal.build_count_index();
+    let indexed = count_all(&cal);
+
+    println!("without index: {:?} per count", scan);
+    println!("with index:    {:?} per count", indexed);
+}
@@ -0,0 +1,37 @@
+//! Compare holiday range queries against a full scan of the holiday set,
+//! run with `cargo bench --bench range_queries`
+use chrono::NaiveDate;
+use std::hint::black_box;
+use std::time::Instant;
+use usec::calendar::{Calendar, UsExchangeCalendar};
+
+const ITERATIONS: u32 = 10_000;
+
+fn main() {
+    let mut usec = UsExchangeCalendar::with_default_range(false);
+    let cal = usec.populate_cal(Some(1950), Some(2050)).get_cal();
+    let start = Calendar::from_ymd(2010, 1, 1);
+    let end = Calendar::from_ymd(2019, 12, 31);
+
+    let now = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(
+            cal.holidays_in_range(black_box(start), black_box(end))
+                .count(),
+        );
+    }
+    let range = now.elapsed();
+
+    let now = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(
+            cal.holidays_in_range(NaiveDate::MIN, NaiveDate::MAX)
+                .filter(|date| *date >= black_box(start) && *date <= black_box(end))
+                .count(),
+        );
+    }
+    let scan = now.elapsed();
+
+    println!("range query: {:?} per query", range / ITERATIONS);
+    println!("full scan:   {:?} per query", scan / ITERATIONS);
+}
@@ -1,4 +1,4 @@
-use usec::calendar::UsExchangeCalendar;
+use usec::prelude::*;
 fn main() {
     let mut sc = UsExchangeCalendar::with_default_range(false);
     sc.populate_cal(Some(2021), Some(2024));
@@ -0,0 +1,13 @@
+//! Deserialize arbitrary bytes as holiday rules and populate a calendar from them, neither
+//! step may panic: rules with out of range parameters yield an error. Run with `cargo +nightly fuzz run holiday_rules fuzz/corpus/holiday_rules`
+//! from the repository root.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use usec::calendar::{Calendar, Holiday};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(rules) = serde_json::from_slice::<Vec<Holiday>>(data) {
+        let _ = Calendar::try_calc_calendar(&rules, 2000, 2002);
+    }
+});
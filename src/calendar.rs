@@ -2,10 +2,16 @@
 //! code borrowed heavily from
 //! <https://github.com/xemwebe/cal-calc>
 
-use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
+use std::fmt;
+use std::ops::{Bound, RangeInclusive};
+use std::sync::Mutex;
 
 /// Specifies the nth week of a month
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
@@ -14,13 +20,49 @@ pub enum NthWeek {
     Second,
     Third,
     Fourth,
+    /// the last occurrence of the weekday in the month
     Last,
+    /// the weekday of the last full week (Monday to Sunday) of the month, differs from
+    /// [`NthWeek::Last`] if the month doesn't end on a Sunday and the weekday falls into the
+    /// trailing partial week
+    LastFullWeek,
 }
 /// Do the half-day holiday check before or after the target date
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub enum HalfCheck {
     Before,
     After,
+    /// both the day before and the day after are half-day holidays
+    Both,
+}
+
+/// How a date falling on a weekend day is moved to a business day
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollConvention {
+    /// the next business day
+    Following,
+    /// the previous business day
+    Preceding,
+}
+
+/// A correction of a computed calendar, registered with [`UsExchangeCalendar::add_override`]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarOverride {
+    /// closed all day, see [`Calendar::add_override_closure`]
+    Closure(NaiveDate),
+    /// half-day holiday closing at the given time, see [`Calendar::add_override_early_close`]
+    EarlyClose(NaiveDate, NaiveTime),
+    /// regular trading day instead of a half-day holiday, see [`Calendar::remove_half_day`]
+    RemoveHalfDay(NaiveDate),
+}
+
+/// When a fixed-date holiday falling on a weekend is observed
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Observance {
+    /// Saturday holidays are observed the Friday before, Sunday ones the Monday after (NYSE)
+    NearestWeekday,
+    /// Saturday and Sunday holidays are both observed the following Monday, never earlier
+    MondayIfWeekend,
 }
 
 /// Types of days when US stocks exchanges are closed
@@ -28,24 +70,77 @@ pub enum HalfCheck {
 pub enum Holiday {
     /// for US exchanges, `Sat` and `Sun`
     WeekDay(Weekday),
-    /// `first` and `last` are the first and last year this day is a holiday (inclusively).
+    /// A weekend day only from year `first` to `last` (inclusively), the day is a business day
+    /// in other years, e.g. Saturdays at the NYSE which traded on Saturdays until 1952
+    /// Markets that added a trading day use `last`, markets that dropped one `first`;
+    /// both bounds are optional in serialized rules.
+    BoundedWeekDay {
+        weekday: Weekday,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        first: Option<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last: Option<i32>,
+    },
+    /// A weekday with a shortened session every week, e.g. `Sun` for some crypto venues.
+    /// The day stays a business day but is reported as half-day holiday.
+    HalfWeekDay(Weekday),
+    /// `first` and `last` are the first and last year this day is a holiday (inclusively),
+    /// `except` lists years within that range in which this day is not a holiday,
+    /// `only_leap_years` restricts the holiday to leap years (`Some(true)`) or other years (`Some(false)`).
+    /// A February 29 holiday only applies in leap years.
     MovableYearlyDay {
         month: u32,
         day: u32,
         first: Option<i32>,
         last: Option<i32>,
         half_check: Option<HalfCheck>,
+        /// per year replacement of `half_check`, e.g. `{"2002": "After"}` for an early close
+        /// after instead of before the holiday, or `{"2013": null}` for no early close at all
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        half_check_years: BTreeMap<i32, Option<HalfCheck>>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        except: Vec<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        only_leap_years: Option<bool>,
+        /// how a date falling on a weekend is observed, [`Observance::NearestWeekday`] if `None`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        observance: Option<Observance>,
+        /// name of the holiday, e.g. "Christmas Day"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
     },
     /// A single holiday which is valid only once in time.
     SingularDay(NaiveDate),
+    /// A single half-day holiday (early close) which is valid only once in time.
+    SingularHalfDay(NaiveDate),
     /// A holiday that is defined in relative days (e.g. -2 for Good Friday) to Easter (Sunday).
+    /// `except` lists years in which the exchange is open on this day anyway,
+    /// `only_leap_years` restricts the holiday to leap years (`Some(true)`) or other years (`Some(false)`),
+    /// `adjust` moves a date landing on a weekend day to a business day (other holidays are not considered),
+    /// the date is kept if every weekday is a weekend day.
     EasterOffset {
         offset: i32,
         first: Option<i32>,
         last: Option<i32>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        except: Vec<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        only_leap_years: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        adjust: Option<RollConvention>,
+        /// half-day holidays next to the holiday, e.g. `Before` for an early close on the
+        /// Thursday before Good Friday
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        half_check: Option<HalfCheck>,
+        /// name of the holiday, e.g. "Christmas Day"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
     },
-    /// A holiday that falls on the nth (or last) weekday of a specific month, e.g. the first Monday in May.
-    /// `first` and `last` are the first and last year this day is a holiday (inclusively).
+    /// A holiday that falls on the nth (or last) weekday of a specific month, e.g. the first Monday in May,
+    /// moved by `offset_days` (e.g. 1 for US Election Day, the Tuesday after the first Monday in November).
+    /// `first` and `last` are the first and last year this day is a holiday (inclusively),
+    /// `except` lists years within that range in which this day is not a holiday,
+    /// `only_leap_years` restricts the holiday to leap years (`Some(true)`) or other years (`Some(false)`).
     MonthWeekday {
         month: u32,
         weekday: Weekday,
@@ -53,36 +148,430 @@ pub enum Holiday {
         first: Option<i32>,
         last: Option<i32>,
         half_check: Option<HalfCheck>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        except: Vec<i32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        only_leap_years: Option<bool>,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        offset_days: i32,
+        /// name of the holiday, e.g. "Christmas Day"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    /// Every `weekday` in the given `months` (1 to 12) is a half-day holiday closing at
+    /// `close_time`, e.g. shortened Friday sessions during summer. Days on which `weekday`
+    /// is a weekend day are skipped.
+    RecurringHalfDay {
+        weekday: Weekday,
+        months: Vec<u32>,
+        close_time: NaiveTime,
+        first: Option<i32>,
+        last: Option<i32>,
+        /// name of the early closes, e.g. "Summer Fridays"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+}
+
+impl Holiday {
+    /// Name of the holiday if the rule has one
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Holiday::MovableYearlyDay { name, .. }
+            | Holiday::EasterOffset { name, .. }
+            | Holiday::MonthWeekday { name, .. }
+            | Holiday::RecurringHalfDay { name, .. } => name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Dates in `year` this rule produces in ascending order: the full-day closures, or the
+    /// half-days for [`Holiday::HalfWeekDay`], [`Holiday::SingularHalfDay`] and
+    /// [`Holiday::RecurringHalfDay`]. Early closes next to a holiday from `half_check` are left
+    /// out, [`Holiday::occurrences`] includes them. `rules` is the rule set this rule belongs
+    /// to, its weekend days affect the dates as in [`Calendar::calc_calendar`] (e.g. the roll
+    /// of an [`Holiday::EasterOffset`] with `adjust`).
+    pub fn effective_dates(&self, rules: &[Holiday], year: i32) -> Vec<NaiveDate> {
+        let half_day_rule = matches!(
+            self,
+            Holiday::HalfWeekDay(_)
+                | Holiday::SingularHalfDay(_)
+                | Holiday::RecurringHalfDay { .. }
+        );
+        let cal = Holiday::calc_around_year(&self.with_weekend_of(rules), year);
+        Calendar::from_ymd(year, 1, 1)
+            .iter_days()
+            .take_while(|date| date.year() == year)
+            .filter(|date| {
+                if half_day_rule {
+                    cal.is_half_holiday(*date)
+                } else {
+                    self.closes_all_day(&cal, *date)
+                }
+            })
+            .collect()
+    }
+
+    /// Dates from `start` to `end` (inclusively, in years) on which this rule closes the market
+    /// all day or early, in ascending order, with the weekend days of `rules` as in
+    /// [`Holiday::effective_dates`]. The dates are computed one year at a time, so wide
+    /// ranges don't have to be held in memory. Panics like [`Calendar::calc_calendar`] if the
+    /// rule can't be computed for a year.
+    pub fn occurrences<'a>(
+        &'a self,
+        rules: &[Holiday],
+        start: i32,
+        end: i32,
+    ) -> impl Iterator<Item = NaiveDate> + 'a {
+        let context = self.with_weekend_of(rules);
+        (start..=end).flat_map(move |year| {
+            let cal = Holiday::calc_around_year(&context, year);
+            Calendar::from_ymd(year, 1, 1)
+                .iter_days()
+                .take_while(move |date| date.year() == year)
+                .filter(move |date| self.closes_all_day(&cal, *date) || cal.is_half_holiday(*date))
+        })
+    }
+
+    /// This rule preceded by the weekend days of `rules`, weekend day rules are taken alone
+    fn with_weekend_of(&self, rules: &[Holiday]) -> Vec<Holiday> {
+        let weekend_rule =
+            |rule: &Holiday| matches!(rule, Holiday::WeekDay(_) | Holiday::BoundedWeekDay { .. });
+        let mut context: Vec<Holiday> = if weekend_rule(self) {
+            vec![]
+        } else {
+            rules
+                .iter()
+                .filter(|rule| weekend_rule(rule))
+                .cloned()
+                .collect()
+        };
+        context.push(self.clone());
+        context
+    }
+
+    /// Returns true if this rule closes the market all day on `date` of a calendar computed
+    /// from [`Holiday::with_weekend_of`]
+    fn closes_all_day(&self, cal: &Calendar, date: NaiveDate) -> bool {
+        match self {
+            Holiday::WeekDay(_) | Holiday::BoundedWeekDay { .. } => cal.is_weekend(date),
+            _ => cal.is_holiday(date),
+        }
+    }
+
+    /// Calendar of `rules` for `year` and its neighbours, so dates of `year` moved there from
+    /// the turn of the year (e.g. an early close on December 31 before New Year's Day) are
+    /// included
+    fn calc_around_year(rules: &[Holiday], year: i32) -> Calendar {
+        Calendar::calc_calendar(rules, year - 1, year + 1)
+    }
+}
+
+/// Status of a single day in a calendar, see [`Calendar::holiday_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolidayStatus {
+    /// full-day holiday on a day that is not a weekend day
+    Holiday,
+    /// half-day holiday, the exchange closes early
+    HalfDay,
+    /// regular business day
+    BusinessDay,
+    /// weekend day, whether or not a holiday falls on it
+    Weekend,
+    /// the date is outside of the range the holidays have been calculated for
+    Uncovered,
+    /// the date is before the market started trading
+    PreInception,
+}
+
+/// Why a date is not a business day, see [`Calendar::closure_reasons`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClosureReason {
+    /// the date is a weekend day
+    Weekend(Weekday),
+    /// the date is a full-day holiday, with its name if the rule that produced it has one
+    /// (see [`Calendar::holiday_name`]) and the index of that rule, `None` for holidays given
+    /// explicitly (see [`Calendar::holiday_source`])
+    Holiday {
+        name: Option<String>,
+        source: Option<usize>,
     },
 }
 
+/// Which end points of a date span are counted, for the span from Monday to Friday of a week
+/// without holidays the counts are given in parentheses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bounds {
+    /// count both `start` and `end` (5)
+    Inclusive,
+    /// count neither `start` nor `end` (3)
+    Exclusive,
+    /// count `start` but not `end` (4)
+    IncludeStart,
+    /// count `end` but not `start` (4), the usual convention for settlement and accrual periods
+    IncludeEnd,
+}
+
+/// Errors returned by the fallible calendar constructors
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A week mask that is not exactly 7 characters of `0` and `1`
+    InvalidWeekmask(String),
+    /// A file or directory could not be read
+    Io { path: String, message: String },
+    /// A rules file does not contain a valid JSON list of holiday rules
+    InvalidRules { path: String, message: String },
+    /// (De)serialization to or from JSON failed
+    Json(String),
+    /// A holiday rule can't be computed for the year, e.g. Easter before 1583
+    UnsupportedYear(i32),
+    /// A holiday rule with a parameter out of range, e.g. month 13
+    InvalidRule(String),
+    /// (De)serialization to or from the binary format of [`Calendar::to_bytes`] failed
+    Binary(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidWeekmask(mask) => write!(
+                f,
+                "invalid week mask {:?}, expected 7 characters of '0' or '1' (Mon to Sun)",
+                mask
+            ),
+            Error::Io { path, message } => write!(f, "failed to read {}: {}", path, message),
+            Error::InvalidRules { path, message } => {
+                write!(f, "invalid holiday rules in {}: {}", path, message)
+            }
+            Error::Json(message) => write!(f, "JSON error: {}", message),
+            Error::UnsupportedYear(year) => {
+                write!(f, "holiday rules can't be computed for year {}", year)
+            }
+            Error::Binary(message) => write!(f, "binary calendar error: {}", message),
+            Error::InvalidRule(message) => write!(f, "invalid holiday rule: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Number of days per kind in a date range, see [`Calendar::range_breakdown`]. Every day is
+/// assigned to exactly one kind, so the counts add up to [`RangeBreakdown::total`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RangeBreakdown {
+    /// business days with a regular session
+    pub business_days: usize,
+    /// full-day holidays that are no weekend days, including days before the inception
+    pub holidays: usize,
+    /// weekend days, including those with a holiday
+    pub weekend_days: usize,
+    /// business days with a shortened session
+    pub half_days: usize,
+}
+
+impl RangeBreakdown {
+    /// Number of calendar days in the range
+    pub fn total(&self) -> usize {
+        self.business_days + self.holidays + self.weekend_days + self.half_days
+    }
+}
+
+/// Daily trading hours of an exchange. A session whose `open` is later than its `close`
+/// wraps past midnight, it opens the evening before its trade date (e.g. CME Globex futures
+/// trading from 18:00 to 17:00 with a one hour maintenance break).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+}
+
+impl Session {
+    pub fn new(open: NaiveTime, close: NaiveTime) -> Session {
+        Session { open, close }
+    }
+
+    /// Returns true if the session starts on the calendar day before its trade date
+    pub fn wraps_midnight(&self) -> bool {
+        self.open > self.close
+    }
+
+    /// Returns the trade date of the session `at` falls in, `None` if `at` lies between two
+    /// sessions. Trading hours are open inclusive and close exclusive.
+    pub fn trade_date(&self, at: NaiveDateTime) -> Option<NaiveDate> {
+        let time = at.time();
+        if self.wraps_midnight() {
+            if time >= self.open {
+                at.date().succ_opt()
+            } else if time < self.close {
+                Some(at.date())
+            } else {
+                None
+            }
+        } else if time >= self.open && time < self.close {
+            Some(at.date())
+        } else {
+            None
+        }
+    }
+}
+
 /// Calendar for arbitrary complex holiday rules
-#[derive(Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Calendar {
     holidays: BTreeSet<NaiveDate>,
     halfdays: BTreeSet<NaiveDate>,
-    weekdays: Vec<Weekday>,
+    /// bit mask of weekend days, bit 0 is Monday
+    #[serde(with = "weekday_mask")]
+    weekdays: u8,
+    /// dates falling on a day of `weekdays` that are no weekend days, as the
+    /// [`Holiday::BoundedWeekDay`] rule for that day doesn't apply in their year
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    weekend_exceptions: BTreeSet<NaiveDate>,
+    /// bit mask of weekdays with a shortened session, never overlaps with `weekdays`
+    #[serde(with = "weekday_mask")]
+    half_weekdays: u8,
+    /// first and last date for which holidays have been calculated
+    range: Option<(NaiveDate, NaiveDate)>,
+    /// first day of trading, earlier dates are no business days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inception: Option<NaiveDate>,
+    /// closing time on half-day holidays
+    early_close: NaiveTime,
+    /// regular open and close if they differ from [`REGULAR_OPEN`] and [`REGULAR_CLOSE`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regular_hours: Option<(NaiveTime, NaiveTime)>,
+    /// closing times of half-day holidays differing from `early_close`
+    #[serde(default)]
+    close_times: BTreeMap<NaiveDate, NaiveTime>,
+    /// index of the rule that produced each full-day holiday, holidays given explicitly (e.g.
+    /// overrides) have no entry
+    #[serde(default)]
+    sources: BTreeMap<NaiveDate, usize>,
+    /// names of full-day holidays produced by named rules
+    #[serde(default)]
+    names: BTreeMap<NaiveDate, String>,
+    #[serde(skip)]
+    count_index: CountIndex,
+}
+
+/// Cumulative business day counts over the covered range, entry `i` is the number of business
+/// days before the `i`th day of the range. Derived data, so it's ignored when comparing calendars.
+#[derive(Debug, Clone, Default)]
+struct CountIndex(Option<Vec<u32>>);
+
+impl PartialEq for CountIndex {
+    fn eq(&self, _other: &CountIndex) -> bool {
+        true
+    }
 }
 
+impl Eq for CountIndex {}
+
 impl Calendar {
     /// Calculate all holidays and recognize weekend days for a given range of years
     /// from `start` to `end` (inclusively). The calculation is performed on the basis
     /// of a vector of holiday rules.
+    ///
+    /// Weekend days come only from [`Holiday::WeekDay`] and [`Holiday::BoundedWeekDay`]
+    /// rules, there's no implicit Saturday/Sunday weekend: rules without them describe a
+    /// market trading every day of the week.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a rule can't be computed for a year of the range, see
+    /// [`Calendar::try_calc_calendar`]
     pub fn calc_calendar(holiday_rules: &[Holiday], start: i32, end: i32) -> Calendar {
+        Self::try_calc_calendar(holiday_rules, start, end).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [`Calendar::calc_calendar`] for the inclusive range of `years`, e.g. `2000..=2050`.
+    /// An empty range yields a calendar without covered range.
+    pub fn calc_calendar_range(holiday_rules: &[Holiday], years: RangeInclusive<i32>) -> Calendar {
+        Self::calc_calendar(holiday_rules, *years.start(), *years.end())
+    }
+
+    /// Same as [`Calendar::calc_calendar`], but returns [`Error::UnsupportedYear`] instead of
+    /// panicking if Easter can't be computed for a year an [`Holiday::EasterOffset`] rule
+    /// applies to (before 1583 or after 9999) or if the offset of a rule moves its date out
+    /// of the range of representable dates, and [`Error::InvalidRule`] for a month or day
+    /// that doesn't exist
+    pub fn try_calc_calendar(
+        holiday_rules: &[Holiday],
+        start: i32,
+        end: i32,
+    ) -> Result<Calendar, Error> {
         let mut holidays = BTreeSet::new();
         let mut halfdays = BTreeSet::new();
-        let mut weekdays = Vec::new();
+        let mut half_weekdays = 0;
+        let mut sources = BTreeMap::new();
+        let mut names = BTreeMap::new();
+        let mut close_times = BTreeMap::new();
+        // weekend days are needed upfront to adjust dates of other rules
+        let weekdays = holiday_rules.iter().fold(0, |weekdays, rule| match rule {
+            Holiday::WeekDay(weekday) | Holiday::BoundedWeekDay { weekday, .. } => {
+                weekdays | weekday_bit(*weekday)
+            }
+            _ => weekdays,
+        });
+        let weekend_exceptions = Self::calc_weekend_exceptions(holiday_rules, start, end);
 
-        for rule in holiday_rules {
+        for (index, rule) in holiday_rules.iter().enumerate() {
+            let mut produced = false;
             match rule {
                 Holiday::SingularDay(date) => {
                     let year = date.year();
                     if year >= start && year <= end {
-                        holidays.insert(*date);
+                        if holidays.insert(*date) {
+                            sources.insert(*date, index);
+                        }
+                        produced = true;
+                    }
+                }
+                Holiday::SingularHalfDay(date) => {
+                    let year = date.year();
+                    if year >= start && year <= end {
+                        halfdays.insert(*date);
+                        produced = true;
+                    }
+                }
+                Holiday::RecurringHalfDay {
+                    weekday,
+                    months,
+                    close_time,
+                    first,
+                    last,
+                    ..
+                } => {
+                    if let Some(month) = months.iter().find(|month| !(1..=12).contains(*month)) {
+                        return Err(Error::InvalidRule(format!("month {}", month)));
+                    }
+                    let (first, last) = Self::calc_first_and_last(start, end, first, last);
+                    let weekend = weekdays & weekday_bit(*weekday) != 0;
+                    for year in first..=last {
+                        for month in months {
+                            let mut date =
+                                nth_weekday_of_month(year, *month, *weekday, &NthWeek::First);
+                            while date.month() == *month {
+                                if !weekend || weekend_exceptions.contains(&date) {
+                                    halfdays.insert(date);
+                                    close_times.insert(date, *close_time);
+                                    produced = true;
+                                }
+                                date += Duration::days(7);
+                            }
+                        }
                     }
                 }
-                Holiday::WeekDay(weekday) => {
-                    weekdays.push(*weekday);
+                Holiday::WeekDay(_) => {
+                    produced = true;
+                }
+                Holiday::BoundedWeekDay { first, last, .. } => {
+                    let (first, last) = Self::calc_first_and_last(start, end, first, last);
+                    produced = first <= last;
+                }
+                Holiday::HalfWeekDay(weekday) => {
+                    half_weekdays |= weekday_bit(*weekday);
+                    produced = true;
                 }
                 // check if prior to 7/4 and 12/25
                 Holiday::MovableYearlyDay {
@@ -91,19 +580,39 @@ impl Calendar {
                     first,
                     last,
                     half_check,
+                    half_check_years,
+                    except,
+                    only_leap_years,
+                    observance,
+                    name,
                 } => {
+                    // any day of a leap year
+                    if NaiveDate::from_ymd_opt(2000, *month, *day).is_none() {
+                        return Err(Error::InvalidRule(format!("month {} day {}", month, day)));
+                    }
                     let (first, last) = Self::calc_first_and_last(start, end, first, last);
-                    for year in first..last + 1 {
-                        let date = Calendar::from_ymd(year, *month, *day);
-                        // if date falls on Saturday, use Friday, if date falls on Sunday, use Monday
+                    let observance = observance.unwrap_or(Observance::NearestWeekday);
+                    for year in (first..last + 1)
+                        .filter(|year| applies_in_year(*year, except, only_leap_years))
+                    {
+                        // February 29 outside of leap years
+                        let Some(date) = NaiveDate::from_ymd_opt(year, *month, *day) else {
+                            continue;
+                        };
+                        // if date falls on Saturday, use Friday (or Monday if observed on Mondays),
+                        // if date falls on Sunday, use Monday
                         let orig_wd = date.weekday();
                         let mut moved_already = false;
-                        let date = match orig_wd {
-                            Weekday::Sat => {
+                        let date = match (orig_wd, observance) {
+                            (Weekday::Sat, Observance::NearestWeekday) => {
                                 moved_already = true;
                                 date.pred_opt().unwrap()
                             }
-                            Weekday::Sun => {
+                            (Weekday::Sat, Observance::MondayIfWeekend) => {
+                                moved_already = true;
+                                date + Duration::days(2)
+                            }
+                            (Weekday::Sun, _) => {
                                 moved_already = true;
                                 date.succ_opt().unwrap()
                             }
@@ -112,8 +621,15 @@ impl Calendar {
                         let (last_date_of_month, last_date_of_year) = accounting_period_end(date);
                         // use the date only if it's not the end of a month or a year
                         if date != last_date_of_month && date != last_date_of_year {
-                            holidays.insert(date);
+                            if holidays.insert(date) {
+                                sources.insert(date, index);
+                                if let Some(name) = name {
+                                    names.insert(date, name.clone());
+                                }
+                            }
+                            produced = true;
                             if !moved_already {
+                                let half_check = half_check_years.get(&year).unwrap_or(half_check);
                                 do_halfday_check(&date, &mut halfdays, half_check);
                             }
                         }
@@ -123,15 +639,41 @@ impl Calendar {
                     offset,
                     first,
                     last,
+                    except,
+                    only_leap_years,
+                    adjust,
+                    half_check,
+                    name,
                 } => {
                     let (first, last) = Self::calc_first_and_last(start, end, first, last);
-                    for year in first..last + 1 {
-                        let easter = computus::gregorian(year).unwrap();
+                    for year in (first..last + 1)
+                        .filter(|year| applies_in_year(*year, except, only_leap_years))
+                    {
+                        let easter =
+                            computus::gregorian(year).map_err(|_| Error::UnsupportedYear(year))?;
                         let easter = Calendar::from_ymd(easter.year, easter.month, easter.day);
-                        let date = easter
+                        let mut date = easter
                             .checked_add_signed(Duration::days(*offset as i64))
-                            .unwrap();
-                        holidays.insert(date);
+                            .ok_or(Error::UnsupportedYear(year))?;
+                        // a week without business days leaves nothing to roll to
+                        while weekdays & weekday_bit(date.weekday()) != 0
+                            && weekdays != ALL_WEEKDAYS
+                        {
+                            date = match adjust {
+                                Some(RollConvention::Following) => date.succ_opt(),
+                                Some(RollConvention::Preceding) => date.pred_opt(),
+                                None => break,
+                            }
+                            .ok_or(Error::UnsupportedYear(year))?;
+                        }
+                        if holidays.insert(date) {
+                            sources.insert(date, index);
+                            if let Some(name) = name {
+                                names.insert(date, name.clone());
+                            }
+                        }
+                        produced = true;
+                        do_halfday_check(&date, &mut halfdays, half_check);
                     }
                 }
                 Holiday::MonthWeekday {
@@ -141,33 +683,164 @@ impl Calendar {
                     first,
                     last,
                     half_check,
+                    except,
+                    only_leap_years,
+                    offset_days,
+                    name,
                 } => {
+                    if !(1..=12).contains(month) {
+                        return Err(Error::InvalidRule(format!("month {}", month)));
+                    }
                     let (first, last) = Self::calc_first_and_last(start, end, first, last);
-                    for year in first..last + 1 {
-                        let day = match nth {
-                            NthWeek::First => 1,
-                            NthWeek::Second => 8,
-                            NthWeek::Third => 15,
-                            NthWeek::Fourth => 22,
-                            NthWeek::Last => last_day_of_month(year, *month),
-                        };
-                        let mut date = Calendar::from_ymd(year, *month, day);
-                        while date.weekday() != *weekday {
-                            date = match nth {
-                                NthWeek::Last => date.pred_opt().unwrap(),
-                                _ => date.succ_opt().unwrap(),
+                    for year in (first..last + 1)
+                        .filter(|year| applies_in_year(*year, except, only_leap_years))
+                    {
+                        let date = nth_weekday_of_month(year, *month, *weekday, nth)
+                            .checked_add_signed(Duration::days(*offset_days as i64))
+                            .ok_or(Error::UnsupportedYear(year))?;
+                        if holidays.insert(date) {
+                            sources.insert(date, index);
+                            if let Some(name) = name {
+                                names.insert(date, name.clone());
                             }
                         }
-                        holidays.insert(date);
+                        produced = true;
                         do_halfday_check(&date, &mut halfdays, half_check);
                     }
                 }
             }
+            warn_if_empty(rule, produced, start, end);
         }
-        Calendar {
+        let range = if start <= end {
+            Some((
+                Calendar::from_ymd(start, 1, 1),
+                Calendar::from_ymd(end, 12, 31),
+            ))
+        } else {
+            None
+        };
+        // a full-day holiday takes precedence over a half-day holiday on the same date
+        halfdays.retain(|date| !holidays.contains(date));
+        close_times.retain(|date, _| halfdays.contains(date));
+        Ok(Calendar {
             holidays,
             halfdays,
             weekdays,
+            weekend_exceptions,
+            half_weekdays: half_weekdays & !weekdays,
+            range,
+            close_times,
+            sources,
+            names,
+            ..Calendar::empty()
+        })
+    }
+
+    /// Calendar without any holidays or weekend days, covering no range and using the default
+    /// hours; the other constructors fill in what they compute on top of it
+    fn empty() -> Calendar {
+        Calendar {
+            holidays: BTreeSet::new(),
+            halfdays: BTreeSet::new(),
+            weekdays: 0,
+            weekend_exceptions: BTreeSet::new(),
+            half_weekdays: 0,
+            range: None,
+            inception: None,
+            early_close: EARLY_CLOSE,
+            regular_hours: None,
+            close_times: BTreeMap::new(),
+            sources: BTreeMap::new(),
+            names: BTreeMap::new(),
+            count_index: CountIndex::default(),
+        }
+    }
+
+    /// Create a calendar from a numpy style week mask and an explicit list of holidays.
+    /// The mask has one character per weekday starting with Monday, `1` marks a trading day
+    /// and `0` a non-trading day, e.g. `"1111100"` for the regular Mon-Fri week.
+    pub fn from_weekmask(mask: &str, holidays: &[NaiveDate]) -> Result<Calendar, Error> {
+        if mask.chars().count() != 7 || mask.chars().any(|c| c != '0' && c != '1') {
+            return Err(Error::InvalidWeekmask(mask.to_string()));
+        }
+        let mut weekdays = 0;
+        let mut weekday = Weekday::Mon;
+        for c in mask.chars() {
+            if c == '0' {
+                weekdays |= weekday_bit(weekday);
+            }
+            weekday = weekday.succ();
+        }
+        Ok(Calendar {
+            holidays: holidays.iter().cloned().collect(),
+            weekdays,
+            ..Calendar::empty()
+        })
+    }
+
+    /// Combine two calendars for a change of rules at `cutover`: dates before it follow
+    /// `before`, dates on or after it follow `after`. Weekend days, regular hours and the
+    /// closing time of half-days are those of `after`; within the covered range of `before`,
+    /// its weekend days and weekly half-days that `after` doesn't share are turned into
+    /// holidays and half-days and its business days on weekend days of `after` are kept as
+    /// business days. Rule provenance is not retained as rule indices would be ambiguous.
+    pub fn with_cutover(before: &Calendar, after: &Calendar, cutover: NaiveDate) -> Calendar {
+        let mut holidays: BTreeSet<NaiveDate> = before.holidays.range(..cutover).copied().collect();
+        let mut halfdays: BTreeSet<NaiveDate> = before.halfdays.range(..cutover).copied().collect();
+        let mut close_times: BTreeMap<NaiveDate, NaiveTime> = BTreeMap::new();
+        if before.early_close != after.early_close {
+            close_times.extend(halfdays.iter().map(|date| (*date, before.early_close)));
+        }
+        close_times.extend(before.close_times.range(..cutover));
+        let mut weekend_exceptions: BTreeSet<NaiveDate> =
+            after.weekend_exceptions.range(cutover..).copied().collect();
+        if let Some((start, end)) = before.range {
+            let extra_half_weekdays = before.half_weekdays & !after.half_weekdays;
+            let end = std::cmp::min(end, cutover.pred_opt().unwrap_or(end));
+            for date in start.iter_days().take_while(|date| *date <= end) {
+                let bit = weekday_bit(date.weekday());
+                let after_weekend = after.weekdays & bit != 0;
+                if before.is_weekend(date) && !after_weekend {
+                    holidays.insert(date);
+                } else if !before.is_weekend(date) && after_weekend {
+                    weekend_exceptions.insert(date);
+                } else if extra_half_weekdays & bit != 0 && !holidays.contains(&date) {
+                    halfdays.insert(date);
+                    close_times.insert(date, before.early_close);
+                }
+            }
+        }
+        let mut names: BTreeMap<NaiveDate, String> = before
+            .names
+            .range(..cutover)
+            .map(|(date, name)| (*date, name.clone()))
+            .collect();
+        names.extend(
+            after
+                .names
+                .range(cutover..)
+                .map(|(date, name)| (*date, name.clone())),
+        );
+        holidays.extend(after.holidays.range(cutover..));
+        halfdays.extend(after.halfdays.range(cutover..));
+        close_times.extend(after.close_times.range(cutover..));
+        let range = match (before.range, after.range) {
+            (Some(before), Some(after)) => Some((before.0.min(after.0), before.1.max(after.1))),
+            (range, None) | (None, range) => range,
+        };
+        Calendar {
+            holidays,
+            halfdays,
+            weekdays: after.weekdays,
+            weekend_exceptions,
+            half_weekdays: after.half_weekdays,
+            range,
+            inception: before.inception.or(after.inception),
+            early_close: after.early_close,
+            regular_hours: after.regular_hours,
+            close_times,
+            names,
+            ..Calendar::empty()
         }
     }
 
@@ -189,6 +862,446 @@ impl Calendar {
         date
     }
 
+    /// Move `n` business days forward (or backward for negative `n`) from `date`. Returns `None`
+    /// instead of panicking if the walk leaves the covered range or the representable dates.
+    /// Calendars without covered range (e.g. from [`Calendar::from_weekmask`]) are unbounded,
+    /// unless every weekday is a weekend day.
+    pub fn checked_add_business_days(&self, date: NaiveDate, n: i64) -> Option<NaiveDate> {
+        if !self.is_within_bounds(date)
+            || (n != 0 && self.range.is_none() && self.weekdays == ALL_WEEKDAYS)
+        {
+            return None;
+        }
+        let mut date = date;
+        for _ in 0..n.unsigned_abs() {
+            loop {
+                date = if n > 0 {
+                    date.succ_opt()?
+                } else {
+                    date.pred_opt()?
+                };
+                if !self.is_within_bounds(date) {
+                    return None;
+                }
+                if self.is_business_day(date) {
+                    break;
+                }
+            }
+        }
+        Some(date)
+    }
+
+    /// Next half-day holiday after `from` with its closing time, `None` if there's none up to
+    /// the end of the covered range
+    pub fn next_early_close(&self, from: NaiveDate) -> Option<(NaiveDate, NaiveTime)> {
+        let (_, last) = self.range?;
+        from.succ_opt()?
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .filter(|date| self.is_business_day(*date))
+            .find_map(|date| self.half_day_close(date).map(|close| (date, close)))
+    }
+
+    /// Calculate the next business day that is not a half-day holiday
+    pub fn next_full_biz_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = self.next_biz_day(date);
+        while self.is_half_holiday(date) {
+            date = self.next_biz_day(date);
+        }
+        date
+    }
+
+    /// Calculate the previous business day that is not a half-day holiday
+    pub fn prev_full_biz_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = self.prev_biz_day(date);
+        while self.is_half_holiday(date) {
+            date = self.prev_biz_day(date);
+        }
+        date
+    }
+
+    /// Returns a copy of this calendar with every full and half-day holiday moved by `days`,
+    /// weekend days and the covered range stay unchanged. Holidays that would be moved out
+    /// of the representable date range are dropped.
+    pub fn shift(&self, days: i64) -> Calendar {
+        let offset = Duration::try_days(days);
+        let shift = |date: &NaiveDate| offset.and_then(|offset| date.checked_add_signed(offset));
+        Calendar {
+            holidays: self.holidays.iter().filter_map(shift).collect(),
+            halfdays: self.halfdays.iter().filter_map(shift).collect(),
+            close_times: self
+                .close_times
+                .iter()
+                .filter_map(|(date, time)| Some((shift(date)?, *time)))
+                .collect(),
+            sources: self
+                .sources
+                .iter()
+                .filter_map(|(date, index)| Some((shift(date)?, *index)))
+                .collect(),
+            names: self
+                .names
+                .iter()
+                .filter_map(|(date, name)| Some((shift(date)?, name.clone())))
+                .collect(),
+            count_index: CountIndex::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a stable 64 bit FNV-1a hash of the holidays, half-day holidays and weekend days,
+    /// which can be stored to check a computed calendar against a known-good one in tests.
+    /// The covered range is not part of the fingerprint.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        feed(&[self.weekdays, self.half_weekdays]);
+        let mut sets = vec![&self.holidays, &self.halfdays];
+        // only fed when present to keep the fingerprints of calendars without exceptions
+        // unchanged from before weekend exceptions existed
+        if !self.weekend_exceptions.is_empty() {
+            sets.push(&self.weekend_exceptions);
+        }
+        for dates in sets {
+            feed(&(dates.len() as u64).to_le_bytes());
+            for date in dates {
+                feed(&date.num_days_from_ce().to_le_bytes());
+            }
+        }
+        hash
+    }
+
+    /// Returns `date` if it is a business day, otherwise the next business day
+    pub fn next_or_same_business_day(&self, date: NaiveDate) -> NaiveDate {
+        if self.is_business_day(date) {
+            date
+        } else {
+            self.next_biz_day(date)
+        }
+    }
+
+    /// Returns `date` if it is a business day, otherwise the next business day. Returns `None`
+    /// if `date` isn't covered or the search leaves the populated range, calendars without
+    /// covered range are unbounded as in [`Calendar::checked_add_business_days`].
+    pub fn next_business_day_on_or_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        if !self.is_within_bounds(date) {
+            None
+        } else if self.is_business_day(date) {
+            Some(date)
+        } else {
+            self.checked_add_business_days(date, 1)
+        }
+    }
+
+    /// Returns `date` if it is a business day, otherwise the previous business day
+    pub fn prev_or_same_business_day(&self, date: NaiveDate) -> NaiveDate {
+        if self.is_business_day(date) {
+            date
+        } else {
+            self.prev_biz_day(date)
+        }
+    }
+
+    /// Returns `date` if it is a business day, otherwise whichever of the next and previous
+    /// business day is fewer calendar days away. `tie` only decides between equally distant
+    /// days, [`RollConvention::Following`] picking the next and [`RollConvention::Preceding`]
+    /// the previous business day; the nearer one always wins otherwise.
+    pub fn closest_business_day(&self, date: NaiveDate, tie: RollConvention) -> NaiveDate {
+        if self.is_business_day(date) {
+            return date;
+        }
+        let next = self.next_biz_day(date);
+        let prev = self.prev_biz_day(date);
+        match (next - date).cmp(&(date - prev)) {
+            Ordering::Less => next,
+            Ordering::Greater => prev,
+            Ordering::Equal => match tie {
+                RollConvention::Following => next,
+                RollConvention::Preceding => prev,
+            },
+        }
+    }
+
+    /// First business day within `range`, `None` if the range has no business day
+    pub fn first_business_day_in(&self, range: RangeInclusive<NaiveDate>) -> Option<NaiveDate> {
+        let (start, end) = range.into_inner();
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .find(|date| self.is_business_day(*date))
+    }
+
+    /// Last business day within `range`, `None` if the range has no business day
+    pub fn last_business_day_in(&self, range: RangeInclusive<NaiveDate>) -> Option<NaiveDate> {
+        let (start, end) = range.into_inner();
+        end.iter_days()
+            .rev()
+            .take_while(|date| *date >= start)
+            .find(|date| self.is_business_day(*date))
+    }
+
+    /// First and last business day of the covered range, `None` if the calendar hasn't been
+    /// calculated over a range or the range has no business day
+    pub fn trading_span(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let (first, last) = self.range?;
+        Some((
+            self.first_business_day_in(first..=last)?,
+            self.last_business_day_in(first..=last)?,
+        ))
+    }
+
+    /// Fraction of the trading year elapsed as of `date`, i.e. the number of business days
+    /// from Jan 1st up to and including `date` divided by the total number of business days
+    /// in that year. Returns 0.0 for a year without business days.
+    pub fn trading_year_fraction(&self, date: NaiveDate) -> f64 {
+        let year = date.year();
+        let year_start = Calendar::from_ymd(year, 1, 1);
+        let year_end = Calendar::from_ymd(year, 12, 31);
+        let total = self.count_business_days(year_start, year_end);
+        if total == 0 {
+            return 0.0;
+        }
+        self.count_business_days(year_start, date) as f64 / total as f64
+    }
+
+    /// Number of business days strictly after `date` up to and including Dec 31st of that year,
+    /// `date` itself is not counted
+    pub fn trading_days_remaining(&self, date: NaiveDate) -> usize {
+        self.business_days_between(date, Calendar::year_end(date), Bounds::IncludeEnd)
+    }
+
+    /// Count the full-day holidays from `start` to `end` (inclusively) per weekday, indexed by
+    /// [`Weekday::num_days_from_monday`]
+    pub fn holiday_weekday_distribution(&self, start: NaiveDate, end: NaiveDate) -> [usize; 7] {
+        let mut distribution = [0; 7];
+        for date in self.holidays_in_range(start, end) {
+            distribution[date.weekday().num_days_from_monday() as usize] += 1;
+        }
+        distribution
+    }
+
+    /// Returns the full-day holidays from `start` to `end` (inclusively) in ascending order
+    pub fn holidays_in_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl DoubleEndedIterator<Item = NaiveDate> + '_ {
+        self.holidays.range(date_range(start, end)).copied()
+    }
+
+    /// Returns the half-day holidays from `start` to `end` (inclusively) in ascending order,
+    /// weekly shortened sessions from [`Holiday::HalfWeekDay`] rules are not included
+    pub fn half_holidays_in_range(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl DoubleEndedIterator<Item = NaiveDate> + '_ {
+        self.halfdays.range(date_range(start, end)).copied()
+    }
+
+    /// Returns the full-day holidays in `year` in ascending order
+    pub fn holidays_for_year(&self, year: i32) -> Vec<NaiveDate> {
+        self.holidays_in_range(
+            Calendar::from_ymd(year, 1, 1),
+            Calendar::from_ymd(year, 12, 31),
+        )
+        .collect()
+    }
+
+    /// Returns the half-day holidays in `year` in ascending order, see
+    /// [`Calendar::half_holidays_in_range`]
+    pub fn half_holidays_for_year(&self, year: i32) -> Vec<NaiveDate> {
+        self.half_holidays_in_range(
+            Calendar::from_ymd(year, 1, 1),
+            Calendar::from_ymd(year, 12, 31),
+        )
+        .collect()
+    }
+
+    /// Count the business days between `start` and `end` regardless of their order, `bounds`
+    /// determines whether the end points themselves are counted.
+    pub fn business_days_between(&self, start: NaiveDate, end: NaiveDate, bounds: Bounds) -> usize {
+        self.signed_business_days_between(start, end, bounds)
+            .unsigned_abs() as usize
+    }
+
+    /// Number of business days left from `from` until `to`, counting `to` but not `from`, e.g.
+    /// 1 from the day before an expiry on the next business day. `None` if `to` is before
+    /// `from` or either date is outside the covered range.
+    pub fn trading_days_until(&self, from: NaiveDate, to: NaiveDate) -> Option<usize> {
+        if to < from || !self.is_covered(from) || !self.is_covered(to) {
+            return None;
+        }
+        Some(self.business_days_between(from, to, Bounds::IncludeEnd))
+    }
+
+    /// Count the business days from `a` to `b`, negative if `a` is after `b`. `bounds`
+    /// determines whether `a` (the start) and `b` (the end) are counted, whatever their order.
+    /// Swapping the arguments negates the count for [`Bounds::Inclusive`] and
+    /// [`Bounds::Exclusive`], [`Bounds::IncludeStart`] and [`Bounds::IncludeEnd`] have to be
+    /// swapped along with them unless both dates are business days or both are not.
+    pub fn signed_business_days_between(&self, a: NaiveDate, b: NaiveDate, bounds: Bounds) -> i64 {
+        if a > b {
+            let bounds = match bounds {
+                Bounds::IncludeStart => Bounds::IncludeEnd,
+                Bounds::IncludeEnd => Bounds::IncludeStart,
+                bounds => bounds,
+            };
+            return -self.signed_business_days_between(b, a, bounds);
+        }
+        let (start, end) = match bounds {
+            Bounds::Inclusive => (Some(a), Some(b)),
+            Bounds::Exclusive => (a.succ_opt(), b.pred_opt()),
+            Bounds::IncludeStart => (Some(a), b.pred_opt()),
+            Bounds::IncludeEnd => (a.succ_opt(), Some(b)),
+        };
+        match (start, end) {
+            (Some(start), Some(end)) => self.count_business_days(start, end) as i64,
+            _ => 0,
+        }
+    }
+
+    /// Count business days from `start` to `end` (inclusively)
+    fn count_business_days(&self, start: NaiveDate, end: NaiveDate) -> usize {
+        if start > end {
+            return 0;
+        }
+        if let (Some(index), Some((first, _))) = (&self.count_index.0, self.range) {
+            if self.is_covered(start) && self.is_covered(end) {
+                let start = (start - first).num_days() as usize;
+                let end = (end - first).num_days() as usize;
+                return (index[end + 1] - index[start]) as usize;
+            }
+        }
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .filter(|date| self.is_business_day(*date))
+            .count()
+    }
+
+    /// Precompute cumulative business day counts over the covered range, so counting business
+    /// days between two covered dates (e.g. [`Calendar::business_days_between`]) becomes a
+    /// constant time lookup. Changing the calendar afterwards discards the index.
+    pub fn build_count_index(&mut self) -> &mut Self {
+        self.count_index = CountIndex(self.range.map(|(first, last)| {
+            let mut count = 0;
+            let mut index = vec![0];
+            for date in first.iter_days().take_while(|date| *date <= last) {
+                if self.is_business_day(date) {
+                    count += 1;
+                }
+                index.push(count);
+            }
+            index
+        }));
+        self
+    }
+
+    /// Position of `date` among the business days of the covered range, starting at 0 for the
+    /// first one. `None` if `date` is not a covered business day. Constant time once
+    /// [`Calendar::build_count_index`] has been called.
+    pub fn business_day_rank(&self, date: NaiveDate) -> Option<u32> {
+        let (first, _) = self.range?;
+        if !self.is_covered(date) || !self.is_business_day(date) {
+            return None;
+        }
+        Some(self.count_business_days(first, date) as u32 - 1)
+    }
+
+    /// Business day at position `rank` of the covered range, inverse of
+    /// [`Calendar::business_day_rank`]. `None` if the range has no more business days.
+    pub fn business_day_at_rank(&self, rank: u32) -> Option<NaiveDate> {
+        let (first, last) = self.range?;
+        match &self.count_index.0 {
+            Some(index) => {
+                // first day with `rank + 1` business days up to and including it
+                let days = index.partition_point(|count| *count <= rank);
+                if days == index.len() {
+                    None
+                } else {
+                    first.checked_add_signed(Duration::days(days as i64 - 1))
+                }
+            }
+            None => first
+                .iter_days()
+                .take_while(|date| *date <= last)
+                .filter(|date| self.is_business_day(*date))
+                .nth(rank as usize),
+        }
+    }
+
+    /// Returns the first business day of `quarter` (1 to 4) in `year`, `None` for any other
+    /// `quarter`
+    pub fn first_business_day_of_quarter(&self, year: i32, quarter: u8) -> Option<NaiveDate> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        let month = 3 * quarter as u32 - 2;
+        Some(self.next_or_same_business_day(Calendar::from_ymd(year, month, 1)))
+    }
+
+    /// Returns the last business day of `quarter` (1 to 4) in `year`, `None` for any other
+    /// `quarter`
+    pub fn last_business_day_of_quarter(&self, year: i32, quarter: u8) -> Option<NaiveDate> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        let month = 3 * quarter as u32;
+        let month_end = Calendar::month_end(Calendar::from_ymd(year, month, 1));
+        Some(self.prev_or_same_business_day(month_end))
+    }
+
+    /// Days of weekdays only covered by [`Holiday::BoundedWeekDay`] rules in years none of
+    /// these rules applies to
+    fn calc_weekend_exceptions(
+        holiday_rules: &[Holiday],
+        start: i32,
+        end: i32,
+    ) -> BTreeSet<NaiveDate> {
+        let mut exceptions = BTreeSet::new();
+        let unbounded = holiday_rules.iter().fold(0, |weekdays, rule| match rule {
+            Holiday::WeekDay(weekday) => weekdays | weekday_bit(*weekday),
+            _ => weekdays,
+        });
+        for rule in holiday_rules {
+            let weekday = match rule {
+                Holiday::BoundedWeekDay { weekday, .. } => *weekday,
+                _ => continue,
+            };
+            if unbounded & weekday_bit(weekday) != 0 {
+                continue;
+            }
+            let applies = |year: i32| {
+                holiday_rules.iter().any(|rule| match rule {
+                    Holiday::BoundedWeekDay {
+                        weekday: other,
+                        first,
+                        last,
+                    } => {
+                        *other == weekday
+                            && first.is_none_or(|first| year >= first)
+                            && last.is_none_or(|last| year <= last)
+                    }
+                    _ => false,
+                })
+            };
+            for year in (start..=end).filter(|year| !applies(*year)) {
+                let mut date = nth_weekday_of_month(year, 1, weekday, &NthWeek::First);
+                while date.year() == year {
+                    exceptions.insert(date);
+                    date += Duration::days(7);
+                }
+            }
+        }
+        exceptions
+    }
+
     fn calc_first_and_last(
         start: i32,
         end: i32,
@@ -206,43 +1319,661 @@ impl Calendar {
         (first, last)
     }
 
-    /// Returns true if the date falls on a weekend
+    /// Returns true if the date falls on a weekend, i.e. on a day of the weekend rules of the
+    /// calendar (see [`Calendar::calc_calendar`]) not restored by a weekend exception
+    #[inline]
     pub fn is_weekend(&self, day: NaiveDate) -> bool {
-        let weekday = day.weekday();
-        for w_day in &self.weekdays {
-            if weekday == *w_day {
-                return true;
-            }
-        }
-        false
+        self.weekdays & weekday_bit(day.weekday()) != 0 && !self.weekend_exceptions.contains(&day)
     }
 
-    /// Returns true if the specified day is a full-day holiday
-    pub fn is_holiday(&self, date: NaiveDate) -> bool {
-        self.holidays.get(&date).is_some()
+    /// Returns the weekend days of this calendar without duplicates, ordered from Monday to Sunday
+    pub fn weekdays(&self) -> Vec<Weekday> {
+        mask_weekdays(self.weekdays)
     }
 
-    /// Returns true if the specified day is a half-day holiday
-    pub fn is_half_holiday(&self, date: NaiveDate) -> bool {
-        self.halfdays.get(&date).is_some()
+    /// Returns the weekdays with a shortened session every week, ordered from Monday to Sunday
+    pub fn half_weekdays(&self) -> Vec<Weekday> {
+        mask_weekdays(self.half_weekdays)
     }
 
-    /// Returns true if the specified day is a business day
-    pub fn is_business_day(&self, date: NaiveDate) -> bool {
-        !self.is_weekend(date) && !self.is_holiday(date)
+    /// Returns the first and last date (inclusively) the holidays of this calendar have been
+    /// calculated for, `None` if the calendar was not calculated from rules over a range of years
+    pub fn covered_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        self.range
     }
 
-    pub fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
-        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    /// Merge the dates of `other`, calculated from the same rules for an adjacent range of
+    /// years, into this calendar and extend the covered range accordingly
+    fn extend_covered(&mut self, other: Calendar) {
+        self.count_index = CountIndex::default();
+        self.holidays.extend(other.holidays);
+        self.halfdays.extend(other.halfdays);
+        self.weekend_exceptions.extend(other.weekend_exceptions);
+        self.close_times.extend(other.close_times);
+        self.sources.extend(other.sources);
+        self.names.extend(other.names);
+        // as in `try_calc_calendar`, e.g. for an early close computed for a new year on a
+        // holiday of the old one
+        self.halfdays.retain(|date| !self.holidays.contains(date));
+        let halfdays = &self.halfdays;
+        self.close_times.retain(|date, _| halfdays.contains(date));
+        self.range = match (self.range, other.range) {
+            (Some((first, last)), Some((other_first, other_last))) => {
+                Some((first.min(other_first), last.max(other_last)))
+            }
+            (range, other_range) => range.or(other_range),
+        };
     }
-}
+
+    /// Returns true if the holidays of `date` have been calculated
+    pub fn is_covered(&self, date: NaiveDate) -> bool {
+        match self.range {
+            Some((first, last)) => date >= first && date <= last,
+            None => false,
+        }
+    }
+
+    /// Returns true if `date` is covered or the calendar has no covered range, e.g. when
+    /// created with [`Calendar::from_weekmask`]
+    fn is_within_bounds(&self, date: NaiveDate) -> bool {
+        self.range.is_none() || self.is_covered(date)
+    }
+
+    /// First day of trading if set, see [`Calendar::set_inception`]
+    pub fn inception(&self) -> Option<NaiveDate> {
+        self.inception
+    }
+
+    /// Set the first day of trading, dates before `inception` are no business days and are
+    /// reported as [`HolidayStatus::PreInception`]
+    pub fn set_inception(&mut self, inception: Option<NaiveDate>) -> &mut Self {
+        self.count_index = CountIndex::default();
+        self.inception = inception;
+        self
+    }
+
+    /// Regular open and close of the sessions, [`REGULAR_OPEN`] to [`REGULAR_CLOSE`] unless
+    /// set with [`Calendar::set_regular_hours`]
+    pub fn regular_hours(&self) -> (NaiveTime, NaiveTime) {
+        self.regular_hours.unwrap_or((REGULAR_OPEN, REGULAR_CLOSE))
+    }
+
+    /// Set the regular open and close of the sessions, e.g. to model historical trading hours
+    pub fn set_regular_hours(&mut self, open: NaiveTime, close: NaiveTime) -> &mut Self {
+        self.regular_hours =
+            Some((open, close)).filter(|hours| *hours != (REGULAR_OPEN, REGULAR_CLOSE));
+        self
+    }
+
+    /// Returns true if `date` is before the first day of trading
+    pub fn is_pre_inception(&self, date: NaiveDate) -> bool {
+        self.inception.is_some_and(|inception| date < inception)
+    }
+
+    /// Returns the status of `date`, dates outside of the covered range are reported as
+    /// [`HolidayStatus::Uncovered`], dates before the inception as
+    /// [`HolidayStatus::PreInception`] and weekend days take precedence over holidays
+    pub fn holiday_status(&self, date: NaiveDate) -> HolidayStatus {
+        if self.is_pre_inception(date) {
+            HolidayStatus::PreInception
+        } else if !self.is_covered(date) {
+            HolidayStatus::Uncovered
+        } else if self.is_weekend(date) {
+            HolidayStatus::Weekend
+        } else if self.is_holiday(date) {
+            HolidayStatus::Holiday
+        } else if self.is_half_holiday(date) {
+            HolidayStatus::HalfDay
+        } else {
+            HolidayStatus::BusinessDay
+        }
+    }
+
+    /// Lists why `date` is not a business day, both the weekend and the holiday if a holiday
+    /// falls on a weekend day. Empty for business days, including half-day holidays.
+    pub fn closure_reasons(&self, date: NaiveDate) -> Vec<ClosureReason> {
+        let mut reasons = Vec::new();
+        if self.is_weekend(date) {
+            reasons.push(ClosureReason::Weekend(date.weekday()));
+        }
+        if self.is_holiday(date) {
+            reasons.push(ClosureReason::Holiday {
+                name: self.holiday_name(date).map(str::to_string),
+                source: self.holiday_source(date),
+            });
+        }
+        reasons
+    }
+
+    /// Name of the full-day holiday on `date` if it was produced by a named rule
+    pub fn holiday_name(&self, date: NaiveDate) -> Option<&str> {
+        self.names.get(&date).map(String::as_str)
+    }
+
+    /// Index of the holiday rule that produced the full-day holiday on `date`, `None` if
+    /// `date` is no holiday or was given explicitly. The first rule wins if several rules
+    /// produce the same date.
+    pub fn holiday_source(&self, date: NaiveDate) -> Option<usize> {
+        self.sources.get(&date).copied()
+    }
+
+    /// Index of the producing rule for every full-day holiday computed from rules
+    pub fn holiday_sources(&self) -> &BTreeMap<NaiveDate, usize> {
+        &self.sources
+    }
+
+    /// Returns the full-day holidays produced by the rule at `index` in ascending order
+    pub fn holidays_from_rule(&self, index: usize) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.sources
+            .iter()
+            .filter(move |(_, source)| **source == index)
+            .map(|(date, _)| *date)
+    }
+
+    /// Status of every day from `start` to `end` (inclusively), see [`Calendar::holiday_status`]
+    pub fn day_timeline(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(NaiveDate, HolidayStatus)> {
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .map(|date| (date, self.holiday_status(date)))
+            .collect()
+    }
+
+    /// Count the days from `start` to `end` (inclusively) per kind. A holiday falling on a
+    /// weekend day is counted as weekend day only, a half-day holiday as half-day only.
+    pub fn range_breakdown(&self, start: NaiveDate, end: NaiveDate) -> RangeBreakdown {
+        let mut breakdown = RangeBreakdown::default();
+        for date in start.iter_days().take_while(|date| *date <= end) {
+            if self.is_weekend(date) {
+                breakdown.weekend_days += 1;
+            } else if !self.is_business_day(date) {
+                breakdown.holidays += 1;
+            } else if self.is_half_holiday(date) {
+                breakdown.half_days += 1;
+            } else {
+                breakdown.business_days += 1;
+            }
+        }
+        breakdown
+    }
+
+    /// Returns true if the specified day is a full-day holiday
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    /// Returns true if the specified day is a half-day holiday, never true for a full-day
+    /// holiday
+    pub fn is_half_holiday(&self, date: NaiveDate) -> bool {
+        self.halfdays.contains(&date)
+            || (self.half_weekdays & weekday_bit(date.weekday()) != 0 && !self.is_holiday(date))
+    }
+
+    /// Returns the closing time if the specified day is a half-day holiday
+    pub fn half_day_close(&self, date: NaiveDate) -> Option<NaiveTime> {
+        if self.is_half_holiday(date) {
+            Some(*self.close_times.get(&date).unwrap_or(&self.early_close))
+        } else {
+            None
+        }
+    }
+
+    /// Business days from `start` to `end` (inclusively), each with its closing time: the
+    /// half-day close on half-day holidays, the regular close otherwise (see
+    /// [`Calendar::regular_hours`])
+    pub fn trading_sessions(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(NaiveDate, NaiveTime)> {
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .filter(|date| self.is_business_day(*date))
+            .map(|date| {
+                let close = self.half_day_close(date).unwrap_or(self.regular_hours().1);
+                (date, close)
+            })
+            .collect()
+    }
+
+    /// Mark `date` as closed all day without recomputing the calendar, e.g. for an unexpected
+    /// closure. Replaces any half-day holiday on that date.
+    pub fn add_override_closure(&mut self, date: NaiveDate) -> &mut Self {
+        self.count_index = CountIndex::default();
+        self.halfdays.remove(&date);
+        self.close_times.remove(&date);
+        self.holidays.insert(date);
+        self
+    }
+
+    /// Mark `date` as half-day holiday closing at `close_time` without recomputing the
+    /// calendar. Replaces any full-day holiday on that date.
+    pub fn add_override_early_close(
+        &mut self,
+        date: NaiveDate,
+        close_time: NaiveTime,
+    ) -> &mut Self {
+        self.count_index = CountIndex::default();
+        self.holidays.remove(&date);
+        self.sources.remove(&date);
+        self.names.remove(&date);
+        self.halfdays.insert(date);
+        self.close_times.insert(date, close_time);
+        self
+    }
+
+    /// Apply a single [`CalendarOverride`]
+    pub fn apply_override(&mut self, calendar_override: &CalendarOverride) -> &mut Self {
+        match *calendar_override {
+            CalendarOverride::Closure(date) => self.add_override_closure(date),
+            CalendarOverride::EarlyClose(date, close_time) => {
+                self.add_override_early_close(date, close_time)
+            }
+            CalendarOverride::RemoveHalfDay(date) => {
+                self.remove_half_day(date);
+                self
+            }
+        }
+    }
+
+    /// Turn a half-day holiday back into a regular trading day, returns true if `date` was a
+    /// half-day. Half-days resulting from the recurring half weekday mask are not affected.
+    pub fn remove_half_day(&mut self, date: NaiveDate) -> bool {
+        self.count_index = CountIndex::default();
+        self.close_times.remove(&date);
+        self.halfdays.remove(&date)
+    }
+
+    /// Returns true if the specified day is a business day. The weekend bit mask check comes
+    /// first, so most non-trading days never reach the holiday set lookup.
+    #[inline]
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_holiday(date) && !self.is_pre_inception(date)
+    }
+
+    /// Business day status of every day from `start` to `end` (inclusive), index `i` being
+    /// `start + i` days. Holidays in the range are walked once alongside the days instead of
+    /// being looked up for each of them.
+    pub fn business_day_mask(&self, start: NaiveDate, end: NaiveDate) -> Vec<bool> {
+        if start > end {
+            return Vec::new();
+        }
+        let mut holidays = self.holidays.range(start..=end).peekable();
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .map(|date| {
+                let holiday = holidays.next_if_eq(&&date).is_some();
+                !holiday && !self.is_weekend(date) && !self.is_pre_inception(date)
+            })
+            .collect()
+    }
+
+    /// Returns true if the specified day is a business day with actual trading, i.e. not a
+    /// half-day holiday closing at or before the regular open (see
+    /// [`Calendar::regular_hours`]). Such zero-length sessions
+    /// (e.g. an exchange opening only to close again right away) stay business days otherwise.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.is_business_day(date)
+            && self
+                .half_day_close(date)
+                .is_none_or(|close| close > self.regular_hours().0)
+    }
+
+    /// Returns [`Calendar::is_business_day`] as closure, e.g. for APIs taking a
+    /// `Fn(NaiveDate) -> bool` filter
+    pub fn business_day_predicate(&self) -> impl Fn(NaiveDate) -> bool + '_ {
+        move |date| self.is_business_day(date)
+    }
+
+    /// Same as [`Calendar::is_business_day`] for the date component of any date-like value,
+    /// e.g. a `NaiveDateTime` or `DateTime<Utc>`
+    pub fn is_business_day_of<D: Datelike>(&self, d: &D) -> bool {
+        self.is_business_day(Calendar::from_ymd(d.year(), d.month(), d.day()))
+    }
+
+    /// Returns true if `at` (local time of the exchange) falls in `session` and the trade date
+    /// of that session is a business day
+    pub fn is_market_open(&self, at: NaiveDateTime, session: &Session) -> bool {
+        match session.trade_date(at) {
+            Some(date) => self.is_business_day(date),
+            None => false,
+        }
+    }
+
+    /// Returns the last calendar day of the month `date` falls in, regardless of business days
+    pub fn month_end(date: NaiveDate) -> NaiveDate {
+        accounting_period_end(date).0
+    }
+
+    /// Returns Dec 31st of the year `date` falls in, regardless of business days
+    pub fn year_end(date: NaiveDate) -> NaiveDate {
+        accounting_period_end(date).1
+    }
+
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+}
+
+/// Bounds for a set range query from `start` to `end` (inclusively), empty if `start` is after `end`
+fn date_range(start: NaiveDate, end: NaiveDate) -> (Bound<NaiveDate>, Bound<NaiveDate>) {
+    if start <= end {
+        (Bound::Included(start), Bound::Included(end))
+    } else {
+        (Bound::Included(start), Bound::Excluded(start))
+    }
+}
+
+/// Weekend mask with every weekday set
+const ALL_WEEKDAYS: u8 = 0x7f;
+
+/// Bit of `weekday` in a weekend mask, Monday being bit 0
+fn weekday_bit(weekday: Weekday) -> u8 {
+    1 << weekday.num_days_from_monday()
+}
+
+/// (De)serialize a weekday bit mask as list of weekdays
+mod weekday_mask {
+    use super::{mask_weekdays, weekday_bit};
+    use chrono::Weekday;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(mask: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        mask_weekdays(*mask).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let weekdays = Vec::<Weekday>::deserialize(deserializer)?;
+        Ok(weekdays
+            .into_iter()
+            .fold(0, |mask, weekday| mask | weekday_bit(weekday)))
+    }
+}
+
+/// Binary form of [`Calendar`], dates are stored as days since January 1st of year 1 (CE)
+#[cfg(feature = "bincode")]
+#[derive(Deserialize, Serialize)]
+struct BinaryCalendar {
+    holidays: Vec<i32>,
+    halfdays: Vec<i32>,
+    weekdays: u8,
+    weekend_exceptions: Vec<i32>,
+    half_weekdays: u8,
+    range: Option<(i32, i32)>,
+    inception: Option<i32>,
+    /// seconds from midnight
+    early_close: u32,
+    regular_hours: Option<(u32, u32)>,
+    close_times: Vec<(i32, u32)>,
+    sources: Vec<(i32, usize)>,
+    /// distinct holiday names, `names` refers to them by index
+    name_table: Vec<String>,
+    names: Vec<(i32, usize)>,
+}
+
+#[cfg(feature = "bincode")]
+impl Calendar {
+    /// Serialize the calendar into a compact binary form, see [`Calendar::from_bytes`]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let day = |date: &NaiveDate| date.num_days_from_ce();
+        let seconds = |time: &NaiveTime| chrono::Timelike::num_seconds_from_midnight(time);
+        let mut name_table: Vec<String> = Vec::new();
+        let mut names = Vec::with_capacity(self.names.len());
+        for (date, name) in &self.names {
+            let index = match name_table.iter().position(|known| known == name) {
+                Some(index) => index,
+                None => {
+                    name_table.push(name.clone());
+                    name_table.len() - 1
+                }
+            };
+            names.push((day(date), index));
+        }
+        let stored = BinaryCalendar {
+            holidays: self.holidays.iter().map(day).collect(),
+            halfdays: self.halfdays.iter().map(day).collect(),
+            weekdays: self.weekdays,
+            weekend_exceptions: self.weekend_exceptions.iter().map(day).collect(),
+            half_weekdays: self.half_weekdays,
+            range: self.range.map(|(first, last)| (day(&first), day(&last))),
+            inception: self.inception.as_ref().map(day),
+            early_close: seconds(&self.early_close),
+            regular_hours: self
+                .regular_hours
+                .map(|(open, close)| (seconds(&open), seconds(&close))),
+            close_times: self
+                .close_times
+                .iter()
+                .map(|(date, time)| (day(date), seconds(time)))
+                .collect(),
+            sources: self
+                .sources
+                .iter()
+                .map(|(date, index)| (day(date), *index))
+                .collect(),
+            name_table,
+            names,
+        };
+        bincode::serialize(&stored).map_err(|err| Error::Binary(err.to_string()))
+    }
+
+    /// Restore a calendar from the binary form created by [`Calendar::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Calendar, Error> {
+        let stored: BinaryCalendar =
+            bincode::deserialize(bytes).map_err(|err| Error::Binary(err.to_string()))?;
+        let invalid = |what: &str| Error::Binary(format!("invalid {}", what));
+        let date =
+            |day: i32| NaiveDate::from_num_days_from_ce_opt(day).ok_or_else(|| invalid("date"));
+        let time = |seconds: u32| {
+            NaiveTime::from_num_seconds_from_midnight_opt(seconds, 0).ok_or_else(|| invalid("time"))
+        };
+        let dates = |days: Vec<i32>| days.into_iter().map(date).collect::<Result<_, _>>();
+        Ok(Calendar {
+            holidays: dates(stored.holidays)?,
+            halfdays: dates(stored.halfdays)?,
+            weekdays: stored.weekdays,
+            weekend_exceptions: dates(stored.weekend_exceptions)?,
+            half_weekdays: stored.half_weekdays,
+            range: match stored.range {
+                Some((first, last)) => Some((date(first)?, date(last)?)),
+                None => None,
+            },
+            inception: stored.inception.map(date).transpose()?,
+            early_close: time(stored.early_close)?,
+            regular_hours: match stored.regular_hours {
+                Some((open, close)) => Some((time(open)?, time(close)?)),
+                None => None,
+            },
+            close_times: stored
+                .close_times
+                .into_iter()
+                .map(|(day, seconds)| Ok((date(day)?, time(seconds)?)))
+                .collect::<Result<_, Error>>()?,
+            sources: stored
+                .sources
+                .into_iter()
+                .map(|(day, index)| Ok((date(day)?, index)))
+                .collect::<Result<_, Error>>()?,
+            names: stored
+                .names
+                .into_iter()
+                .map(|(day, index)| {
+                    let name = stored
+                        .name_table
+                        .get(index)
+                        .ok_or_else(|| invalid("name"))?;
+                    Ok((date(day)?, name.clone()))
+                })
+                .collect::<Result<_, Error>>()?,
+            count_index: CountIndex::default(),
+        })
+    }
+}
+
+/// Weekdays in a weekend mask, ordered from Monday to Sunday
+fn mask_weekdays(mask: u8) -> Vec<Weekday> {
+    let mut weekday = Weekday::Mon;
+    let mut weekdays = Vec::new();
+    for _ in 0..7 {
+        if mask & weekday_bit(weekday) != 0 {
+            weekdays.push(weekday);
+        }
+        weekday = weekday.succ();
+    }
+    weekdays
+}
+
+/// Warn about a rule that doesn't produce any date from `start` to `end`, usually the result of
+/// `first`/`last` bounds outside of the populated range
+#[cfg(feature = "logging")]
+fn warn_if_empty(rule: &Holiday, produced: bool, start: i32, end: i32) {
+    if !produced {
+        log::warn!(
+            "holiday rule {:?} produces no dates from {} to {}",
+            rule,
+            start,
+            end
+        );
+    }
+}
+
+#[cfg(not(feature = "logging"))]
+fn warn_if_empty(_rule: &Holiday, _produced: bool, _start: i32, _end: i32) {}
+
+fn is_zero(n: &i32) -> bool {
+    *n == 0
+}
+
+/// Returns true if a recurring rule with the `except` and `only_leap_years` restrictions
+/// applies in `year`
+fn applies_in_year(year: i32, except: &[i32], only_leap_years: &Option<bool>) -> bool {
+    !except.contains(&year) && only_leap_years.is_none_or(|leap| is_leap_year(year) == leap)
+}
+
+/// Common queries of trading calendars, for code generic over [`Calendar`],
+/// [`UsExchangeCalendar`] or calendars implemented elsewhere
+pub trait TradingCalendar {
+    /// Returns true if `date` is a business day
+    fn is_business_day(&self, date: NaiveDate) -> bool;
+
+    /// Returns true if `date` is a full-day holiday
+    fn is_holiday(&self, date: NaiveDate) -> bool;
+
+    /// Returns true if `date` is a half-day holiday
+    fn is_half_holiday(&self, date: NaiveDate) -> bool;
+
+    /// Calculate the next business day
+    fn next_biz_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date.succ_opt().unwrap();
+        while !self.is_business_day(date) {
+            date = date.succ_opt().unwrap();
+        }
+        date
+    }
+
+    /// Calculate the previous business day
+    fn prev_biz_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date.pred_opt().unwrap();
+        while !self.is_business_day(date) {
+            date = date.pred_opt().unwrap();
+        }
+        date
+    }
+}
+
+impl TradingCalendar for Calendar {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        Calendar::is_business_day(self, date)
+    }
+
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        Calendar::is_holiday(self, date)
+    }
+
+    fn is_half_holiday(&self, date: NaiveDate) -> bool {
+        Calendar::is_half_holiday(self, date)
+    }
+}
+
+impl TradingCalendar for UsExchangeCalendar {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.cal.is_business_day(date)
+    }
+
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.cal.is_holiday(date)
+    }
+
+    fn is_half_holiday(&self, date: NaiveDate) -> bool {
+        self.cal.is_half_holiday(date)
+    }
+}
+
+/// Extension trait to filter an iterator of dates down to the business days of a calendar,
+/// e.g. `start.iter_days().take(30).filter_business_days(&cal)`
+pub trait BusinessDayFilter: Iterator<Item = NaiveDate> + Sized {
+    fn filter_business_days(self, cal: &Calendar) -> BusinessDays<'_, Self>;
+}
+
+impl<I: Iterator<Item = NaiveDate>> BusinessDayFilter for I {
+    fn filter_business_days(self, cal: &Calendar) -> BusinessDays<'_, Self> {
+        BusinessDays { iter: self, cal }
+    }
+}
+
+/// Iterator returned by [`BusinessDayFilter::filter_business_days`]
+#[derive(Debug, Clone)]
+pub struct BusinessDays<'a, I> {
+    iter: I,
+    cal: &'a Calendar,
+}
+
+impl<I: Iterator<Item = NaiveDate>> Iterator for BusinessDays<'_, I> {
+    type Item = NaiveDate;
+
+    fn next(&mut self) -> Option<NaiveDate> {
+        let cal = self.cal;
+        self.iter.find(|date| cal.is_business_day(*date))
+    }
+}
 
 /// Returns true if the specified year is a leap year (i.e. Feb 29th exists for this year)
 pub fn is_leap_year(year: i32) -> bool {
     NaiveDate::from_ymd_opt(year, 2, 29).is_some()
 }
 
-/// Returns ending accounting period (end of month, end of year)
+/// Returns the number of days in the specified year (365 or 366)
+pub fn days_in_year(year: i32) -> u32 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+/// Returns the number of days in `quarter` (1 to 4) of the specified year, `None` for any other
+/// `quarter`
+pub fn days_in_quarter(year: i32, quarter: u8) -> Option<u32> {
+    if !(1..=4).contains(&quarter) {
+        return None;
+    }
+    let first_month = 3 * quarter as u32 - 2;
+    Some(
+        (first_month..first_month + 3)
+            .map(|month| last_day_of_month(year, month))
+            .sum(),
+    )
+}
+
+/// Returns the ends of the accounting periods `date` falls in as a tuple of
+/// (last date of the month, last date of the year), see also [`Calendar::month_end`] and
+/// [`Calendar::year_end`]
 pub fn accounting_period_end(date: NaiveDate) -> (NaiveDate, NaiveDate) {
     let month = date.month();
     let year = date.year();
@@ -250,8 +1981,8 @@ pub fn accounting_period_end(date: NaiveDate) -> (NaiveDate, NaiveDate) {
         .unwrap_or_else(|| Calendar::from_ymd(year + 1, 1, 1))
         .pred_opt()
         .unwrap();
-    let last_date_of_year = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
-    return (last_date_of_month, last_date_of_year);
+    let last_date_of_year = Calendar::from_ymd(year, 12, 31);
+    (last_date_of_month, last_date_of_year)
 }
 
 pub fn do_halfday_check(
@@ -266,15 +1997,21 @@ pub fn do_halfday_check(
             if weekday == Weekday::Mon {
                 return;
             }
-            let prior = date.pred_opt().unwrap();
-            halfdays.insert(prior);
+            if let Some(prior) = date.pred_opt() {
+                halfdays.insert(prior);
+            }
         }
         Some(HalfCheck::After) => {
             if weekday == Weekday::Fri {
                 return;
             }
-            let next = date.succ_opt().unwrap();
-            halfdays.insert(next);
+            if let Some(next) = date.succ_opt() {
+                halfdays.insert(next);
+            }
+        }
+        Some(HalfCheck::Both) => {
+            do_halfday_check(date, halfdays, &Some(HalfCheck::Before));
+            do_halfday_check(date, halfdays, &Some(HalfCheck::After));
         }
     }
 }
@@ -288,120 +2025,407 @@ pub fn last_day_of_month(year: i32, month: u32) -> u32 {
         .day()
 }
 
-/// Calendar specific to US stock exchanges
-#[derive(Debug, Clone)]
+/// The `nth` `weekday` of `month`. The result always stays within the target month: the
+/// search starts on the 1st, 8th, 15th or 22nd day and moves forward at most 6 days (28th at
+/// the latest) or, for [`NthWeek::Last`], starts on the last day (28th at the earliest) and
+/// moves backward at most 6 days (22nd at the earliest). For [`NthWeek::LastFullWeek`] the
+/// week ends on the last Sunday (22nd at the earliest), so it starts on the 16th at the earliest.
+fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, nth: &NthWeek) -> NaiveDate {
+    let target = weekday.num_days_from_monday() as i64;
+    match nth {
+        NthWeek::LastFullWeek => {
+            let sunday = nth_weekday_of_month(year, month, Weekday::Sun, &NthWeek::Last);
+            sunday - Duration::days(6 - target)
+        }
+        NthWeek::Last => {
+            let date = Calendar::from_ymd(year, month, last_day_of_month(year, month));
+            let back = (date.weekday().num_days_from_monday() as i64 - target).rem_euclid(7);
+            date - Duration::days(back)
+        }
+        _ => {
+            let day = match nth {
+                NthWeek::First => 1,
+                NthWeek::Second => 8,
+                NthWeek::Third => 15,
+                _ => 22,
+            };
+            let date = Calendar::from_ymd(year, month, day);
+            let ahead = (target - date.weekday().num_days_from_monday() as i64).rem_euclid(7);
+            date + Duration::days(ahead)
+        }
+    }
+}
+
+/// Opening time of the regular session
+pub const REGULAR_OPEN: NaiveTime = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+/// Closing time of the regular session
+pub const REGULAR_CLOSE: NaiveTime = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+/// Closing time on half-day holidays
+pub const EARLY_CLOSE: NaiveTime = NaiveTime::from_hms_opt(13, 0, 0).unwrap();
+
+/// First year populated by default
+pub const DEFAULT_START_YEAR: i32 = 2000;
+/// Last year populated by default, unless the current year is within [`DEFAULT_YEARS_AHEAD`] of it
+pub const DEFAULT_END_YEAR: i32 = 2050;
+/// Minimum number of years after the current year populated by default
+pub const DEFAULT_YEARS_AHEAD: i32 = 10;
+/// Names of the holidays in [`default_us_holiday_rules`] in calendar order
+pub const US_HOLIDAY_NAMES: &[&str] = &[
+    "New Year's Day",
+    "Martin Luther King Jr. Day",
+    "Washington's Birthday",
+    "Good Friday",
+    "Memorial Day",
+    "Juneteenth National Independence Day",
+    "Independence Day",
+    "Labor Day",
+    "Thanksgiving Day",
+    "Christmas Day",
+];
+
+/// Last year populated by default, the later of [`DEFAULT_END_YEAR`] and the current year
+/// plus [`DEFAULT_YEARS_AHEAD`]
+pub fn default_end_year() -> i32 {
+    std::cmp::max(DEFAULT_END_YEAR, Local::now().year() + DEFAULT_YEARS_AHEAD)
+}
+
+/// Default US exchange holiday rules, including weekends. One-off closures of a single
+/// exchange are not included, see [`nyse_special_closures`].
+pub fn default_us_holiday_rules() -> Vec<Holiday> {
+    vec![
+        // Saturdays
+        Holiday::WeekDay(Weekday::Sat),
+        // Sundays
+        Holiday::WeekDay(Weekday::Sun),
+        // New Year's day
+        Holiday::MovableYearlyDay {
+            month: 1,
+            day: 1,
+            first: None,
+            last: None,
+            half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: Some("New Year's Day".to_string()),
+        },
+        // MLK, 3rd Monday of January
+        Holiday::MonthWeekday {
+            month: 1,
+            weekday: Weekday::Mon,
+            nth: NthWeek::Third,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: Some("Martin Luther King Jr. Day".to_string()),
+        },
+        // President's Day
+        Holiday::MonthWeekday {
+            month: 2,
+            weekday: Weekday::Mon,
+            nth: NthWeek::Third,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: Some("Washington's Birthday".to_string()),
+        },
+        // Good Friday
+        Holiday::EasterOffset {
+            offset: -2,
+            first: Some(2000),
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: None,
+            half_check: None,
+            name: Some("Good Friday".to_string()),
+        },
+        // Memorial Day
+        Holiday::MonthWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+            nth: NthWeek::Last,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: Some("Memorial Day".to_string()),
+        },
+        // Juneteenth National Independence Day
+        Holiday::MovableYearlyDay {
+            month: 6,
+            day: 19,
+            first: Some(2022),
+            last: None,
+            half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: Some("Juneteenth National Independence Day".to_string()),
+        },
+        // Independence Day, NYSE closes early (1:00 PM) on July 3rd when July 4th falls on
+        // Tuesday to Friday. There's no early close when July 4th is a Monday or when the
+        // holiday is observed on Friday July 3rd (Saturday) or Monday July 5th (Sunday).
+        // Years in which the exchange deviates from this go into `half_check_years`.
+        Holiday::MovableYearlyDay {
+            month: 7,
+            day: 4,
+            first: None,
+            last: None,
+            half_check: Some(HalfCheck::Before),
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: Some("Independence Day".to_string()),
+        },
+        // Labour Day
+        Holiday::MonthWeekday {
+            month: 9,
+            weekday: Weekday::Mon,
+            nth: NthWeek::First,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: Some("Labor Day".to_string()),
+        },
+        // Thanksgiving Day
+        Holiday::MonthWeekday {
+            month: 11,
+            weekday: Weekday::Thu,
+            nth: NthWeek::Fourth,
+            first: None,
+            last: None,
+            half_check: Some(HalfCheck::After),
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: Some("Thanksgiving Day".to_string()),
+        },
+        // Christmas Day, observed on Friday Dec 24th when on Saturday and on Monday Dec 26th when
+        // on Sunday. NYSE closes early on Dec 24th when Christmas falls on Tuesday to Friday, there
+        // is no early close when Christmas is a Monday or observed on another day. The month and
+        // year end guard doesn't apply as the observed date is never Dec 31st.
+        Holiday::MovableYearlyDay {
+            month: 12,
+            day: 25,
+            first: None,
+            last: None,
+            half_check: Some(HalfCheck::Before),
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: Some("Christmas Day".to_string()),
+        },
+    ]
+}
+
+/// Unscheduled full-day closures of the NYSE since 2001
+pub fn nyse_special_closures() -> Vec<Holiday> {
+    vec![
+        // September 11 attacks
+        Holiday::SingularDay(Calendar::from_ymd(2001, 9, 11)),
+        Holiday::SingularDay(Calendar::from_ymd(2001, 9, 12)),
+        Holiday::SingularDay(Calendar::from_ymd(2001, 9, 13)),
+        Holiday::SingularDay(Calendar::from_ymd(2001, 9, 14)),
+        // National Days of Mourning for Presidents Reagan, Ford, Bush and Carter
+        Holiday::SingularDay(Calendar::from_ymd(2004, 6, 11)),
+        Holiday::SingularDay(Calendar::from_ymd(2007, 1, 2)),
+        Holiday::SingularDay(Calendar::from_ymd(2018, 12, 5)),
+        Holiday::SingularDay(Calendar::from_ymd(2025, 1, 9)),
+        // Hurricane Sandy
+        Holiday::SingularDay(Calendar::from_ymd(2012, 10, 29)),
+        Holiday::SingularDay(Calendar::from_ymd(2012, 10, 30)),
+    ]
+}
+
+/// Calendar specific to US stock exchanges. Only the rules and the populated years are
+/// serialized, the calendar is recomputed from them when deserializing.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(from = "StoredUsExchangeCalendar", into = "StoredUsExchangeCalendar")]
 pub struct UsExchangeCalendar {
     cal: Calendar,
     holiday_rules: Vec<Holiday>,
+    early_close: NaiveTime,
+    regular_open: NaiveTime,
+    regular_close: NaiveTime,
+    inception: Option<NaiveDate>,
+    overrides: Vec<CalendarOverride>,
+}
+
+/// Serialized form of [`UsExchangeCalendar`]
+#[derive(Deserialize, Serialize)]
+struct StoredUsExchangeCalendar {
+    holiday_rules: Vec<Holiday>,
+    early_close: NaiveTime,
+    /// regular open and close if they differ from [`REGULAR_OPEN`] and [`REGULAR_CLOSE`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regular_hours: Option<(NaiveTime, NaiveTime)>,
+    /// first and last populated year, `None` for a calendar that hasn't been populated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    years: Option<(i32, i32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inception: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    overrides: Vec<CalendarOverride>,
+}
+
+impl From<UsExchangeCalendar> for StoredUsExchangeCalendar {
+    fn from(uscal: UsExchangeCalendar) -> StoredUsExchangeCalendar {
+        StoredUsExchangeCalendar {
+            years: uscal
+                .cal
+                .covered_range()
+                .map(|(start, end)| (start.year(), end.year())),
+            holiday_rules: uscal.holiday_rules,
+            early_close: uscal.early_close,
+            regular_hours: Some((uscal.regular_open, uscal.regular_close))
+                .filter(|hours| *hours != (REGULAR_OPEN, REGULAR_CLOSE)),
+            inception: uscal.inception,
+            overrides: uscal.overrides,
+        }
+    }
+}
+
+impl From<StoredUsExchangeCalendar> for UsExchangeCalendar {
+    fn from(stored: StoredUsExchangeCalendar) -> UsExchangeCalendar {
+        let mut uscal = UsExchangeCalendar::from_rules(stored.holiday_rules, false);
+        uscal.with_early_close(stored.early_close);
+        if let Some((open, close)) = stored.regular_hours {
+            uscal.with_regular_hours(open, close);
+        }
+        uscal.with_inception(stored.inception);
+        uscal.overrides = stored.overrides;
+        if let Some((start, end)) = stored.years {
+            uscal.populate_cal(Some(start), Some(end));
+        }
+        uscal
+    }
 }
 
 impl UsExchangeCalendar {
-    /// NYSE holiday calendar as of 2022
+    /// US exchange holiday calendar as of 2022
     /// create a new US Exchange calendar with default rules, populate the
-    /// calendar with default range (2000-2050) if `populate` is set to `true`
+    /// calendar with default range (see [`UsExchangeCalendar::populate_cal`]) if `populate` is set to `true`
     pub fn with_default_range(populate: bool) -> UsExchangeCalendar {
-        let mut holiday_rules = vec![
-            // Saturdays
-            Holiday::WeekDay(Weekday::Sat),
-            // Sundays
-            Holiday::WeekDay(Weekday::Sun),
-            // New Year's day
-            Holiday::MovableYearlyDay {
-                month: 1,
-                day: 1,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-            // MLK, 3rd Monday of January
-            Holiday::MonthWeekday {
-                month: 1,
-                weekday: Weekday::Mon,
-                nth: NthWeek::Third,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-            // President's Day
-            Holiday::MonthWeekday {
-                month: 2,
-                weekday: Weekday::Mon,
-                nth: NthWeek::Third,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-            // Good Friday
-            Holiday::EasterOffset {
-                offset: -2,
-                first: Some(2000),
-                last: None,
-            },
-            // Memorial Day
+        let mut holiday_rules = default_us_holiday_rules();
+        if let Ok(additional_rules) = env::var("ADDITIONAL_RULES") {
+            let mut additional_rules: Vec<Holiday> =
+                serde_json::from_str(&additional_rules).unwrap();
+            holiday_rules.append(&mut additional_rules);
+        }
+        UsExchangeCalendar::from_rules(holiday_rules, populate)
+    }
+
+    /// NYSE holiday calendar, the default rules plus the [`nyse_special_closures`]. Like
+    /// [`UsExchangeCalendar::with_default_range`], the calendar is populated with the default
+    /// range if `populate` is set to `true`.
+    pub fn nyse(populate: bool) -> UsExchangeCalendar {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.holiday_rules.append(&mut nyse_special_closures());
+        if populate {
+            uscal.populate_cal(None, None);
+        }
+        uscal
+    }
+
+    /// Calendar populated from `before` years ago through `after` years ahead of the current
+    /// year, keeping the covered range relative to the clock at construction time
+    pub fn with_rolling_window(before: u32, after: u32) -> UsExchangeCalendar {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        let year = Local::now().year();
+        uscal.populate_cal(Some(year - before as i32), Some(year + after as i32));
+        uscal
+    }
+
+    /// Bond market calendar following the SIFMA holiday recommendations, early closes are at
+    /// 2:00 PM instead of the 1:00 PM of the stock exchanges and include the Thursday before
+    /// Good Friday and New Year's Eve. The New Year's Eve early close only applies when New
+    /// Year's Day falls on Tuesday through Friday, the years it is observed on another day are
+    /// left as full sessions.
+    pub fn sifma(populate: bool) -> UsExchangeCalendar {
+        let mut holiday_rules = default_us_holiday_rules();
+        for rule in holiday_rules.iter_mut() {
+            match rule {
+                Holiday::EasterOffset {
+                    offset: -2,
+                    half_check,
+                    ..
+                }
+                | Holiday::MovableYearlyDay {
+                    month: 1,
+                    day: 1,
+                    half_check,
+                    ..
+                } => {
+                    *half_check = Some(HalfCheck::Before);
+                }
+                _ => {}
+            }
+        }
+        holiday_rules.append(&mut vec![
+            // Columbus Day
             Holiday::MonthWeekday {
-                month: 5,
+                month: 10,
                 weekday: Weekday::Mon,
-                nth: NthWeek::Last,
+                nth: NthWeek::Second,
                 first: None,
                 last: None,
                 half_check: None,
+                except: vec![],
+                only_leap_years: None,
+                offset_days: 0,
+                name: Some("Columbus Day".to_string()),
             },
-            // Juneteenth National Independence Day
-            Holiday::MovableYearlyDay {
-                month: 6,
-                day: 19,
-                first: Some(2022),
-                last: None,
-                half_check: None,
-            },
-            // Independence Day
+            // Veterans Day
             Holiday::MovableYearlyDay {
-                month: 7,
-                day: 4,
-                first: None,
-                last: None,
-                half_check: Some(HalfCheck::Before),
-            },
-            // Labour Day
-            Holiday::MonthWeekday {
-                month: 9,
-                weekday: Weekday::Mon,
-                nth: NthWeek::First,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-            // Thanksgiving Day
-            Holiday::MonthWeekday {
                 month: 11,
-                weekday: Weekday::Thu,
-                nth: NthWeek::Fourth,
-                first: None,
-                last: None,
-                half_check: Some(HalfCheck::After),
-            },
-            // Chrismas Day
-            Holiday::MovableYearlyDay {
-                month: 12,
-                day: 25,
+                day: 11,
                 first: None,
                 last: None,
-                half_check: Some(HalfCheck::Before),
+                half_check: None,
+                half_check_years: BTreeMap::new(),
+                except: vec![],
+                only_leap_years: None,
+                observance: None,
+                name: Some("Veterans Day".to_string()),
             },
-            Holiday::SingularDay(Calendar::from_ymd(2001, 9, 11)),
-        ];
-        let additional_rules = env::var("ADDITIONAL_RULES");
-        if additional_rules.is_ok() {
-            let mut additional_rules: Vec<Holiday> =
-                serde_json::from_str(&additional_rules.unwrap()).unwrap();
-            holiday_rules.append(&mut additional_rules);
+        ]);
+        let mut sc = UsExchangeCalendar::from_rules(holiday_rules, false);
+        sc.with_early_close(NaiveTime::from_hms_opt(14, 0, 0).unwrap());
+        if populate {
+            sc.populate_cal(None, None);
         }
-        let cal = Calendar {
-            holidays: BTreeSet::new(),
-            halfdays: BTreeSet::new(),
-            weekdays: Vec::new(),
+        sc
+    }
+
+    /// create a new calendar from arbitrary holiday rules, populate the
+    /// calendar with default range (see [`UsExchangeCalendar::populate_cal`]) if `populate` is set to `true`.
+    /// The rules have to include the weekend days, e.g. `Holiday::WeekDay(Weekday::Sat)`.
+    pub fn from_rules(holiday_rules: Vec<Holiday>, populate: bool) -> UsExchangeCalendar {
+        let mut sc = UsExchangeCalendar {
+            cal: Calendar::empty(),
+            holiday_rules,
+            early_close: EARLY_CLOSE,
+            regular_open: REGULAR_OPEN,
+            regular_close: REGULAR_CLOSE,
+            inception: None,
+            overrides: vec![],
         };
-        let mut sc = UsExchangeCalendar { cal, holiday_rules };
         if populate {
             sc.populate_cal(None, None);
         }
@@ -414,265 +2438,3249 @@ impl UsExchangeCalendar {
         self
     }
 
-    /// populate calendar for given `start` and `end` years (inclusively, defaults to 2000 and 2050 if None,
-    /// None are given)
+    /// Returns true if the rule list contains `holiday` already
+    pub fn has_rule(&self, holiday: &Holiday) -> bool {
+        self.holiday_rules.contains(holiday)
+    }
+
+    /// Override the regular session hours, [`REGULAR_OPEN`] to [`REGULAR_CLOSE`] by default,
+    /// e.g. to model historical trading hours
+    pub fn with_regular_hours(&mut self, open: NaiveTime, close: NaiveTime) -> &mut Self {
+        self.regular_open = open;
+        self.regular_close = close;
+        self.cal.set_regular_hours(open, close);
+        self
+    }
+
+    /// Override the closing time on half-day holidays without an explicit close time,
+    /// [`EARLY_CLOSE`] by default
+    pub fn with_early_close(&mut self, close: NaiveTime) -> &mut Self {
+        self.early_close = close;
+        self.cal.early_close = close;
+        self
+    }
+
+    /// Set the first day of trading of the market (e.g. Feb 8th 1971 for NASDAQ), earlier
+    /// dates are reported as [`HolidayStatus::PreInception`]
+    pub fn with_inception(&mut self, inception: Option<NaiveDate>) -> &mut Self {
+        self.inception = inception;
+        self.cal.set_inception(inception);
+        self
+    }
+
+    /// Pin a published list of early-close dates, added as [`Holiday::SingularHalfDay`] rules.
+    /// A calendar populated already is repopulated for the same years.
+    pub fn with_explicit_half_days(&mut self, dates: Vec<NaiveDate>) -> &mut Self {
+        self.holiday_rules
+            .extend(dates.into_iter().map(Holiday::SingularHalfDay));
+        if let Some((start, end)) = self.cal.covered_range() {
+            self.populate_cal(Some(start.year()), Some(end.year()));
+        }
+        self
+    }
+
+    /// populate calendar for given `start` and `end` years (inclusively, defaults to
+    /// [`DEFAULT_START_YEAR`] and [`default_end_year`] if None, None are given)
     pub fn populate_cal(&mut self, start: Option<i32>, end: Option<i32>) -> &mut Self {
-        let start = start.unwrap_or(2000);
-        let end = end.unwrap_or(2050);
-        self.cal = Calendar::calc_calendar(&self.holiday_rules, start, end);
+        self.try_populate_cal(start, end)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [`UsExchangeCalendar::populate_cal`], but returns an error instead of panicking
+    /// if the rules can't be computed for the years, see [`Calendar::try_calc_calendar`]
+    pub fn try_populate_cal(
+        &mut self,
+        start: Option<i32>,
+        end: Option<i32>,
+    ) -> Result<&mut Self, Error> {
+        let start = start.unwrap_or(DEFAULT_START_YEAR);
+        let end = end.unwrap_or_else(default_end_year);
+        self.cal = Calendar::try_calc_calendar(&self.holiday_rules, start, end)?;
+        self.cal.early_close = self.early_close;
+        self.cal
+            .set_regular_hours(self.regular_open, self.regular_close);
+        self.cal.inception = self.inception;
+        for calendar_override in &self.overrides {
+            self.cal.apply_override(calendar_override);
+        }
+        Ok(self)
+    }
+
+    /// Register a correction of the computed calendar and apply it right away. Overrides are
+    /// re-applied in the order of registration whenever the calendar is populated, so they
+    /// always win over the rules.
+    pub fn add_override(&mut self, calendar_override: CalendarOverride) -> &mut Self {
+        self.cal.apply_override(&calendar_override);
+        self.overrides.push(calendar_override);
         self
     }
 
+    /// Copy of this calendar recomputed from the current rules for the same years, with the
+    /// registered overrides applied on top
+    pub fn clone_with_rules_reapplied(&self) -> UsExchangeCalendar {
+        let mut uscal = self.clone();
+        if let Some((start, end)) = self.cal.covered_range() {
+            uscal.populate_cal(Some(start.year()), Some(end.year()));
+        }
+        uscal
+    }
+
+    /// Make sure `year` is covered by computing only the missing years between the covered
+    /// range and `year`, so the covered range stays contiguous. Populates just `year` if the
+    /// calendar hasn't been populated yet. Registered overrides are re-applied.
+    pub fn ensure_year(&mut self, year: i32) -> &mut Self {
+        self.try_ensure_year(year)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as [`UsExchangeCalendar::ensure_year`], but returns an error instead of panicking
+    /// if the rules can't be computed for the missing years, the calendar is left unchanged then
+    pub fn try_ensure_year(&mut self, year: i32) -> Result<&mut Self, Error> {
+        let missing = match self.cal.covered_range() {
+            None => {
+                return self.try_populate_cal(Some(year), Some(year));
+            }
+            Some((first, _)) if year < first.year() => year..=first.year() - 1,
+            Some((_, last)) if year > last.year() => last.year() + 1..=year,
+            Some(_) => return Ok(self),
+        };
+        let added =
+            Calendar::try_calc_calendar(&self.holiday_rules, *missing.start(), *missing.end())?;
+        self.cal.extend_covered(added);
+        for calendar_override in &self.overrides {
+            self.cal.apply_override(calendar_override);
+        }
+        Ok(self)
+    }
+
+    /// Consume the calendar builder and return the calendar populated for `start` to `end`,
+    /// defaults as in [`UsExchangeCalendar::populate_cal`]
+    pub fn build_calendar(mut self, start: Option<i32>, end: Option<i32>) -> Calendar {
+        self.populate_cal(start, end);
+        self.cal
+    }
+
     pub fn get_cal(&self) -> Calendar {
         self.cal.clone()
     }
+
+    /// Returns the trading hours on `date`, `None` if the exchange is closed all day
+    pub fn session(&self, date: NaiveDate) -> Option<Session> {
+        let (open, regular_close) = self.cal.regular_hours();
+        if !self.cal.is_business_day(date) {
+            None
+        } else if let Some(close) = self.cal.half_day_close(date) {
+            Some(Session::new(open, close))
+        } else {
+            Some(Session::new(open, regular_close))
+        }
+    }
+
+    /// Returns true if `at` (local time of the exchange) falls in the session of its date,
+    /// see [`UsExchangeCalendar::session`]
+    pub fn is_market_open(&self, at: NaiveDateTime) -> bool {
+        self.session(at.date())
+            .is_some_and(|session| session.trade_date(at).is_some())
+    }
+
+    /// Advance `start` by `dur` of trading time, i.e. only time within the sessions of
+    /// [`UsExchangeCalendar::session`] counts. A `start` outside of the session is moved to
+    /// the next open first. Session hours are interpreted as wall-clock time in the time zone
+    /// of `start`, which therefore should be the exchange's local time zone, and sessions
+    /// wrapping past midnight don't count. Returns `None` if there's no trading time in a
+    /// whole year (e.g. every weekday is a weekend day) or the result falls into a gap of the
+    /// time zone.
+    pub fn add_business_time<Tz: TimeZone>(
+        &self,
+        start: DateTime<Tz>,
+        dur: Duration,
+    ) -> Option<DateTime<Tz>> {
+        let tz = start.timezone();
+        let mut now = start.naive_local();
+        let mut remaining = std::cmp::max(dur, Duration::zero());
+        let mut idle_days = 0;
+        while idle_days <= 366 {
+            let date = now.date();
+            idle_days += 1;
+            if let Some(session) = self.session(date) {
+                let open = date.and_time(session.open);
+                let close = date.and_time(session.close);
+                if now < open {
+                    now = open;
+                }
+                if now < close {
+                    if remaining <= close - now {
+                        return tz.from_local_datetime(&(now + remaining)).earliest();
+                    }
+                    remaining -= close - now;
+                    idle_days = 0;
+                }
+            }
+            now = date.succ_opt()?.and_time(NaiveTime::MIN);
+        }
+        None
+    }
+
+    /// Closures and early closes in `year` as text, one line per date in ascending order, e.g.
+    /// `2025-11-28 Day after Thanksgiving Day (early close 1:00 PM)`. Half-days are named after
+    /// the holiday following or preceding them. A year that hasn't been populated is computed
+    /// from the rules for the report.
+    pub fn schedule_report(&self, year: i32) -> String {
+        let populated;
+        let cal = if self.cal.is_covered(Calendar::from_ymd(year, 1, 1))
+            && self.cal.is_covered(Calendar::from_ymd(year, 12, 31))
+        {
+            &self.cal
+        } else {
+            populated = self.clone().build_calendar(Some(year), Some(year));
+            &populated
+        };
+        let mut days: Vec<(NaiveDate, String)> = cal
+            .holidays_for_year(year)
+            .into_iter()
+            .map(|date| {
+                let name = cal.holiday_name(date).unwrap_or("Holiday");
+                (date, format!("{} {} (closed)", date, name))
+            })
+            .collect();
+        days.extend(cal.half_holidays_for_year(year).into_iter().map(|date| {
+            let name = match (
+                date.succ_opt().and_then(|next| cal.holiday_name(next)),
+                date.pred_opt().and_then(|prev| cal.holiday_name(prev)),
+            ) {
+                (Some(name), _) => format!("Day before {}", name),
+                (None, Some(name)) => format!("Day after {}", name),
+                (None, None) => "Early close".to_string(),
+            };
+            let close = cal.half_day_close(date).unwrap_or(self.early_close);
+            let line = format!(
+                "{} {} (early close {})",
+                date,
+                name,
+                close.format("%-I:%M %p")
+            );
+            (date, line)
+        }));
+        days.sort();
+        days.into_iter().map(|(_, line)| line + "\n").collect()
+    }
+
+    /// Turn this calendar into a [`LazyCalendar`] with the same rules, early close time,
+    /// regular hours, inception and overrides
+    pub fn into_lazy(self) -> LazyCalendar {
+        let mut lazy = LazyCalendar::new(self.holiday_rules);
+        lazy.early_close = self.early_close;
+        lazy.regular_hours = (self.regular_open, self.regular_close);
+        lazy.inception = self.inception;
+        lazy.overrides = self.overrides;
+        lazy
+    }
+}
+
+/// Calendar computing the holidays of a year on the first query of a date in that year
+/// instead of requiring [`UsExchangeCalendar::populate_cal`] upfront
+#[derive(Debug)]
+pub struct LazyCalendar {
+    holiday_rules: Vec<Holiday>,
+    early_close: NaiveTime,
+    regular_hours: (NaiveTime, NaiveTime),
+    inception: Option<NaiveDate>,
+    /// applied to every computed year in the order of registration
+    overrides: Vec<CalendarOverride>,
+    /// calendar per queried year, each computed for the year before and after as well, so
+    /// holidays observed across the turn of the year are included
+    years: Mutex<BTreeMap<i32, Calendar>>,
+}
+
+impl LazyCalendar {
+    /// Lazy calendar for arbitrary holiday rules, half-days closing at 1:00 PM
+    pub fn new(holiday_rules: Vec<Holiday>) -> LazyCalendar {
+        LazyCalendar {
+            holiday_rules,
+            early_close: EARLY_CLOSE,
+            regular_hours: (REGULAR_OPEN, REGULAR_CLOSE),
+            inception: None,
+            overrides: vec![],
+            years: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Years computed so far in ascending order
+    pub fn populated_years(&self) -> Vec<i32> {
+        self.years.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Returns the closing time if `date` is a half-day holiday
+    pub fn half_day_close(&self, date: NaiveDate) -> Option<NaiveTime> {
+        self.with_year(date, |cal| cal.half_day_close(date))
+    }
+
+    fn with_year<T>(&self, date: NaiveDate, query: impl FnOnce(&Calendar) -> T) -> T {
+        let year = date.year();
+        let mut years = self.years.lock().unwrap();
+        let cal = years.entry(year).or_insert_with(|| {
+            let mut cal = Calendar::calc_calendar(&self.holiday_rules, year - 1, year + 1);
+            cal.early_close = self.early_close;
+            cal.set_regular_hours(self.regular_hours.0, self.regular_hours.1);
+            cal.set_inception(self.inception);
+            for calendar_override in &self.overrides {
+                cal.apply_override(calendar_override);
+            }
+            cal
+        });
+        query(cal)
+    }
+}
+
+impl TradingCalendar for LazyCalendar {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        self.with_year(date, |cal| cal.is_business_day(date))
+    }
+
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.with_year(date, |cal| cal.is_holiday(date))
+    }
+
+    fn is_half_holiday(&self, date: NaiveDate) -> bool {
+        self.with_year(date, |cal| cal.is_half_holiday(date))
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn make_cal() -> Calendar {
+        let usec = UsExchangeCalendar::with_default_range(true);
+        usec.get_cal()
+    }
+
+    #[test]
+    fn fixed_dates_calendar() {
+        let holidays = vec![
+            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 20)),
+            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 24)),
+            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 25)),
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2019, 2019);
+
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 20)));
+        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 21)));
+        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 22)));
+        // weekend
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 23)));
+        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2019, 11, 23)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2019, 11, 23)));
+        // weekend and holiday
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 24)));
+        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2019, 11, 24)));
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2019, 11, 24)));
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 25)));
+        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 26)));
+    }
+
+    #[test]
+    fn test_from_weekmask() {
+        // four-day week, Fridays off
+        let cal = Calendar::from_weekmask("1111000", &[Calendar::from_ymd(2022, 3, 2)]).unwrap();
+        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2022, 3, 4)));
+        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2022, 3, 6)));
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2022, 3, 2)));
+        assert_eq!(
+            cal.next_biz_day(Calendar::from_ymd(2022, 3, 3)),
+            Calendar::from_ymd(2022, 3, 7)
+        );
+
+        assert_eq!(
+            Calendar::from_weekmask("111110", &[]).unwrap_err(),
+            Error::InvalidWeekmask("111110".to_string())
+        );
+        assert!(Calendar::from_weekmask("11111x0", &[]).is_err());
+    }
+
+    #[test]
+    fn test_movable_yearly_day() {
+        let holidays = vec![Holiday::MovableYearlyDay {
+            month: 1,
+            day: 1,
+            first: None,
+            last: None,
+            half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: None,
+        }];
+        let cal = Calendar::calc_calendar(&holidays, 2021, 2022);
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2021, 12, 31)));
+    }
+
+    #[test]
+    /// Good Friday example
+    fn test_easter_offset() {
+        let holidays = vec![Holiday::EasterOffset {
+            offset: -2,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: None,
+            half_check: None,
+            name: None,
+        }];
+        let cal = Calendar::calc_calendar(&holidays, 2021, 2022);
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2021, 4, 2)));
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2022, 4, 15)));
+    }
+
+    #[test]
+    fn test_month_weekday() {
+        let holidays = vec![
+            // MLK
+            Holiday::MonthWeekday {
+                month: 1,
+                weekday: Weekday::Mon,
+                nth: NthWeek::Third,
+                first: None,
+                last: None,
+                half_check: None,
+                except: vec![],
+                only_leap_years: None,
+                offset_days: 0,
+                name: None,
+            },
+            // President's Day
+            Holiday::MonthWeekday {
+                month: 2,
+                weekday: Weekday::Mon,
+                nth: NthWeek::Third,
+                first: None,
+                last: None,
+                half_check: None,
+                except: vec![],
+                only_leap_years: None,
+                offset_days: 0,
+                name: None,
+            },
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 1, 17)));
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 2, 21)));
+    }
+
+    #[test]
+    /// Testing serialization and deserialization of holidays definitions
+    fn serialize_cal_definition() {
+        let holidays = vec![
+            Holiday::MonthWeekday {
+                month: 11,
+                weekday: Weekday::Mon,
+                nth: NthWeek::First,
+                first: None,
+                last: None,
+                half_check: None,
+                except: vec![],
+                only_leap_years: None,
+                offset_days: 0,
+                name: None,
+            },
+            Holiday::MovableYearlyDay {
+                month: 11,
+                day: 1,
+                first: Some(2016),
+                last: None,
+                half_check: None,
+                half_check_years: BTreeMap::new(),
+                except: vec![],
+                only_leap_years: None,
+                observance: None,
+                name: None,
+            },
+            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 25)),
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::EasterOffset {
+                offset: -2,
+                first: None,
+                last: None,
+                except: vec![],
+                only_leap_years: None,
+                adjust: None,
+                half_check: None,
+                name: None,
+            },
+        ];
+        let json = serde_json::to_string_pretty(&holidays).unwrap();
+        assert_eq!(
+            json,
+            r#"[
+  {
+    "MonthWeekday": {
+      "month": 11,
+      "weekday": "Mon",
+      "nth": "First",
+      "first": null,
+      "last": null,
+      "half_check": null
+    }
+  },
+  {
+    "MovableYearlyDay": {
+      "month": 11,
+      "day": 1,
+      "first": 2016,
+      "last": null,
+      "half_check": null
+    }
+  },
+  {
+    "SingularDay": "2019-11-25"
+  },
+  {
+    "WeekDay": "Sat"
+  },
+  {
+    "EasterOffset": {
+      "offset": -2,
+      "first": null,
+      "last": null
+    }
+  }
+]"#
+        );
+        let holidays2: Vec<Holiday> = serde_json::from_str(&json).unwrap();
+        assert_eq!(holidays.len(), holidays2.len());
+        for i in 0..holidays.len() {
+            assert_eq!(holidays[i], holidays2[i]);
+        }
+    }
+
+    #[test]
+    fn test_usexchange_calendar_empty() {
+        let sc = UsExchangeCalendar::with_default_range(false);
+        let c = sc.get_cal();
+        assert!(c.holidays.is_empty());
+        assert!(c.halfdays.is_empty());
+        assert!(c.weekdays().is_empty());
+    }
+
+    #[test]
+    fn test_usexchange_calendar_populated() {
+        let sc = UsExchangeCalendar::with_default_range(true);
+        let c = sc.get_cal();
+        assert!(!c.holidays.is_empty());
+        assert!(!c.halfdays.is_empty());
+        assert!(!c.weekdays().is_empty());
+        assert!(c.is_holiday(Calendar::from_ymd(2021, 1, 1)));
+        assert_eq!(false, c.is_holiday(Calendar::from_ymd(2021, 12, 31)))
+    }
+
+    #[test]
+    fn test_usexchange_calendar_with_new_rule() {
+        // imaginary holiday, let's call it March Madness Day
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        let holiday = Holiday::MonthWeekday {
+            month: 3,
+            weekday: Weekday::Wed,
+            nth: NthWeek::Third,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: None,
+        };
+        sc.add_holiday_rule(holiday).populate_cal(None, None);
+        let c = sc.get_cal();
+        assert_eq!(true, c.is_holiday(Calendar::from_ymd(2022, 3, 16)));
+    }
+
+    #[test]
+    fn test_is_trading_date() {
+        let cal = make_cal();
+        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 4, 18)), false);
+        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 4, 19)), true);
+        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 1, 1)), false);
+    }
+
+    #[test]
+    fn test_is_partial_trading_date() {
+        let cal = make_cal();
+        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2027, 12, 23)), false);
+        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2026, 7, 2)), false);
+        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2021, 11, 26)), true);
+        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2022, 5, 12)), false);
+    }
+
+    #[test]
+    fn test_prev_biz_day() {
+        let cal = make_cal();
+        assert_eq!(
+            cal.prev_biz_day(Calendar::from_ymd(2021, 1, 18)),
+            Calendar::from_ymd(2021, 1, 15)
+        );
+        assert_eq!(
+            cal.prev_biz_day(Calendar::from_ymd(2021, 4, 19)),
+            Calendar::from_ymd(2021, 4, 16)
+        );
+        assert_eq!(
+            cal.prev_biz_day(Calendar::from_ymd(2021, 8, 9)),
+            Calendar::from_ymd(2021, 8, 6)
+        );
+    }
+
+    #[test]
+    fn test_next_biz_day() {
+        let cal = make_cal();
+        assert_eq!(
+            cal.next_biz_day(Calendar::from_ymd(2021, 4, 16)),
+            Calendar::from_ymd(2021, 4, 19)
+        );
+        assert_eq!(
+            cal.next_biz_day(Calendar::from_ymd(2021, 4, 19)),
+            Calendar::from_ymd(2021, 4, 20)
+        );
+        assert_eq!(
+            cal.next_biz_day(Calendar::from_ymd(2021, 4, 2)),
+            Calendar::from_ymd(2021, 4, 5)
+        );
+    }
+
+    #[test]
+    fn test_or_same_business_day() {
+        let cal = make_cal();
+        // regular business day stays put
+        let date = Calendar::from_ymd(2021, 4, 19);
+        assert_eq!(cal.next_or_same_business_day(date), date);
+        assert_eq!(cal.prev_or_same_business_day(date), date);
+        // Good Friday
+        let date = Calendar::from_ymd(2021, 4, 2);
+        assert_eq!(
+            cal.next_or_same_business_day(date),
+            Calendar::from_ymd(2021, 4, 5)
+        );
+        assert_eq!(
+            cal.prev_or_same_business_day(date),
+            Calendar::from_ymd(2021, 4, 1)
+        );
+    }
+
+    #[test]
+    fn test_trading_year_fraction() {
+        let cal = make_cal();
+        // 124 of the 252 trading days of 2021 are in the first half of the year
+        let fraction = cal.trading_year_fraction(Calendar::from_ymd(2021, 6, 30));
+        assert!((fraction - 124.0 / 252.0).abs() < 1e-12);
+        assert_eq!(
+            cal.trading_year_fraction(Calendar::from_ymd(2021, 1, 1)),
+            0.0
+        );
+        assert_eq!(
+            cal.trading_year_fraction(Calendar::from_ymd(2021, 12, 31)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_full_biz_day() {
+        let cal = make_cal();
+        // Thanksgiving 2021 is followed by an early close on Friday
+        let thanksgiving = Calendar::from_ymd(2021, 11, 25);
+        assert_eq!(
+            cal.next_biz_day(thanksgiving),
+            Calendar::from_ymd(2021, 11, 26)
+        );
+        assert_eq!(
+            cal.next_full_biz_day(thanksgiving),
+            Calendar::from_ymd(2021, 11, 29)
+        );
+        assert_eq!(
+            cal.prev_full_biz_day(Calendar::from_ymd(2021, 11, 29)),
+            Calendar::from_ymd(2021, 11, 24)
+        );
+    }
+
+    #[test]
+    fn test_weekdays_deduplicated() {
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sat),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(cal.weekdays(), vec![Weekday::Sat, Weekday::Sun]);
+        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2022, 3, 5)));
+        assert_eq!(false, cal.is_weekend(Calendar::from_ymd(2022, 3, 4)));
+        // rule order and duplicates don't affect equality
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+        ];
+        assert_eq!(cal, Calendar::calc_calendar(&holidays, 2022, 2022));
+    }
+
+    #[test]
+    fn test_independence_day_early_close() {
+        let cal = make_cal();
+        // (year, observed holiday, early close)
+        let expected = [
+            (2022, (7, 4), None),         // Monday
+            (2023, (7, 4), Some((7, 3))), // Tuesday
+            (2024, (7, 4), Some((7, 3))), // Thursday
+            (2025, (7, 4), Some((7, 3))), // Friday
+            (2026, (7, 3), None),         // Saturday
+            (2027, (7, 5), None),         // Sunday
+            (2028, (7, 4), Some((7, 3))), // Tuesday
+            (2029, (7, 4), Some((7, 3))), // Wednesday
+        ];
+        for (year, (month, day), early_close) in expected {
+            assert!(cal.is_holiday(Calendar::from_ymd(year, month, day)));
+            let halfdays: Vec<NaiveDate> = Calendar::from_ymd(year, 6, 28)
+                .iter_days()
+                .take(14)
+                .filter(|date| cal.is_half_holiday(*date))
+                .collect();
+            let early_close = early_close
+                .map(|(month, day)| vec![Calendar::from_ymd(year, month, day)])
+                .unwrap_or_default();
+            assert_eq!(halfdays, early_close, "{}", year);
+            for date in early_close {
+                assert_eq!(cal.half_day_close(date), NaiveTime::from_hms_opt(13, 0, 0));
+            }
+        }
+
+        // per year replacement of the early close
+        let rules = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::MovableYearlyDay {
+                month: 7,
+                day: 4,
+                first: None,
+                last: None,
+                half_check: Some(HalfCheck::Before),
+                half_check_years: BTreeMap::from([(2024, Some(HalfCheck::After)), (2025, None)]),
+                except: vec![],
+                only_leap_years: None,
+                observance: None,
+                name: None,
+            },
+        ];
+        let cal = Calendar::calc_calendar(&rules, 2023, 2025);
+        assert!(cal.is_half_holiday(Calendar::from_ymd(2023, 7, 3)));
+        assert!(!cal.is_half_holiday(Calendar::from_ymd(2024, 7, 3)));
+        assert!(cal.is_half_holiday(Calendar::from_ymd(2024, 7, 5)));
+        assert!(!cal.is_half_holiday(Calendar::from_ymd(2025, 7, 3)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2025, 7, 3)));
+
+        let json = serde_json::to_string(&rules).unwrap();
+        assert!(json.contains(r#""half_check_years":{"2024":"After","2025":null}"#));
+        let restored: Vec<Holiday> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, rules);
+    }
+
+    #[test]
+    fn test_covered_range() {
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        assert_eq!(sc.get_cal().covered_range(), None);
+        sc.populate_cal(Some(2021), Some(2024));
+        let c = sc.get_cal();
+        assert_eq!(
+            c.covered_range(),
+            Some((
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2024, 12, 31)
+            ))
+        );
+        assert_eq!(true, c.is_covered(Calendar::from_ymd(2024, 12, 31)));
+        assert_eq!(false, c.is_covered(Calendar::from_ymd(2025, 1, 1)));
+        assert_eq!(false, c.is_covered(Calendar::from_ymd(2020, 12, 31)));
+    }
+
+    #[test]
+    fn test_rule_except_years() {
+        let holidays = vec![Holiday::EasterOffset {
+            offset: -2,
+            first: None,
+            last: None,
+            except: vec![2022],
+            only_leap_years: None,
+            adjust: None,
+            half_check: None,
+            name: None,
+        }];
+        let cal = Calendar::calc_calendar(&holidays, 2021, 2023);
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2021, 4, 2)));
+        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2022, 4, 15)));
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2023, 4, 7)));
+
+        let json = r#"[{"EasterOffset": {"offset": -2, "first": null, "last": null}}]"#;
+        let holidays: Vec<Holiday> = serde_json::from_str(json).unwrap();
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2022, 4, 15)));
+    }
+
+    #[test]
+    fn test_add_business_time() {
+        let sc = UsExchangeCalendar::with_default_range(true);
+        let et = FixedOffset::west_opt(5 * 3600).unwrap();
+        let at = |y, m, d, h, min| et.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+        // 2 hours left on Wednesday, 6 more on Thursday
+        assert_eq!(
+            sc.add_business_time(at(2022, 3, 2, 14, 0), Duration::hours(8)),
+            Some(at(2022, 3, 3, 15, 30))
+        );
+        // starting before the open
+        assert_eq!(
+            sc.add_business_time(at(2022, 3, 2, 6, 0), Duration::hours(1)),
+            Some(at(2022, 3, 2, 10, 30))
+        );
+        // skips the weekend
+        assert_eq!(
+            sc.add_business_time(at(2022, 3, 4, 15, 0), Duration::hours(2)),
+            Some(at(2022, 3, 7, 10, 30))
+        );
+        // skips Thanksgiving and stops at the early close on the day after
+        assert_eq!(
+            sc.add_business_time(at(2021, 11, 24, 15, 0), Duration::hours(5)),
+            Some(at(2021, 11, 29, 10, 0))
+        );
+        // no sessions at all
+        let closed = UsExchangeCalendar::from_rules(
+            [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]
+            .map(Holiday::WeekDay)
+            .to_vec(),
+            true,
+        );
+        assert_eq!(
+            closed.add_business_time(at(2022, 3, 2, 14, 0), Duration::hours(1)),
+            None
+        );
+        let mut inverted = UsExchangeCalendar::with_default_range(true);
+        inverted.with_regular_hours(
+            NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        );
+        assert_eq!(
+            inverted.add_business_time(at(2022, 3, 2, 14, 0), Duration::hours(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overnight_session() {
+        let cal = make_cal();
+        let globex = Session::new(
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        );
+        let at = |y, m, d, h| Calendar::from_ymd(y, m, d).and_hms_opt(h, 0, 0).unwrap();
+        assert!(globex.wraps_midnight());
+        // Monday 02:00 belongs to the session opened on Sunday evening
+        assert_eq!(
+            globex.trade_date(at(2022, 3, 7, 2)),
+            Some(Calendar::from_ymd(2022, 3, 7))
+        );
+        assert_eq!(
+            globex.trade_date(at(2022, 3, 6, 18)),
+            Some(Calendar::from_ymd(2022, 3, 7))
+        );
+        assert_eq!(true, cal.is_market_open(at(2022, 3, 7, 2), &globex));
+        assert_eq!(true, cal.is_market_open(at(2022, 3, 6, 19), &globex));
+        // Sunday afternoon and the daily maintenance break
+        assert_eq!(false, cal.is_market_open(at(2022, 3, 6, 12), &globex));
+        assert_eq!(globex.trade_date(at(2022, 3, 7, 17)), None);
+        // Friday evening opens a Saturday session, Thursday evening before Good Friday a holiday one
+        assert_eq!(false, cal.is_market_open(at(2022, 3, 4, 19), &globex));
+        assert_eq!(false, cal.is_market_open(at(2022, 4, 14, 19), &globex));
+
+        let regular = Session::new(REGULAR_OPEN, REGULAR_CLOSE);
+        assert_eq!(false, regular.wraps_midnight());
+        assert_eq!(true, cal.is_market_open(at(2022, 3, 7, 10), &regular));
+        assert_eq!(false, cal.is_market_open(at(2022, 3, 7, 16), &regular));
+    }
+
+    #[test]
+    fn test_month_end_year_end() {
+        let leap = Calendar::from_ymd(2024, 2, 10);
+        assert_eq!(Calendar::month_end(leap), Calendar::from_ymd(2024, 2, 29));
+        assert_eq!(Calendar::year_end(leap), Calendar::from_ymd(2024, 12, 31));
+        assert_eq!(
+            Calendar::month_end(Calendar::from_ymd(2023, 2, 28)),
+            Calendar::from_ymd(2023, 2, 28)
+        );
+        assert_eq!(
+            Calendar::month_end(Calendar::from_ymd(2100, 2, 1)),
+            Calendar::from_ymd(2100, 2, 28)
+        );
+        let december = Calendar::from_ymd(2022, 12, 1);
+        assert_eq!(
+            accounting_period_end(december),
+            (
+                Calendar::from_ymd(2022, 12, 31),
+                Calendar::from_ymd(2022, 12, 31)
+            )
+        );
+        assert_eq!(last_day_of_month(2000, 2), 29);
+        assert_eq!(last_day_of_month(2022, 12), 31);
+    }
+
+    #[test]
+    fn test_business_days_between() {
+        let cal = make_cal();
+        // Thursday before Good Friday to the Tuesday after (2021)
+        let start = Calendar::from_ymd(2021, 4, 1);
+        let end = Calendar::from_ymd(2021, 4, 6);
+        assert_eq!(cal.business_days_between(start, end, Bounds::Inclusive), 3);
+        assert_eq!(cal.business_days_between(start, end, Bounds::Exclusive), 1);
+        assert_eq!(
+            cal.business_days_between(start, end, Bounds::IncludeStart),
+            2
+        );
+        assert_eq!(cal.business_days_between(start, end, Bounds::IncludeEnd), 2);
+        assert_eq!(
+            cal.business_days_between(start, start, Bounds::Inclusive),
+            1
+        );
+        assert_eq!(
+            cal.business_days_between(start, start, Bounds::Exclusive),
+            0
+        );
+        assert_eq!(cal.business_days_between(end, start, Bounds::Inclusive), 3);
+    }
+
+    #[test]
+    fn test_sifma_early_close() {
+        let bonds = UsExchangeCalendar::sifma(true);
+        let stocks = UsExchangeCalendar::with_default_range(true);
+        let day_after_thanksgiving = Calendar::from_ymd(2021, 11, 26);
+        assert_eq!(
+            bonds.get_cal().half_day_close(day_after_thanksgiving),
+            NaiveTime::from_hms_opt(14, 0, 0)
+        );
+        assert_eq!(
+            bonds.session(day_after_thanksgiving).unwrap().close,
+            NaiveTime::from_hms_opt(14, 0, 0).unwrap()
+        );
+        assert_eq!(
+            stocks.get_cal().half_day_close(day_after_thanksgiving),
+            NaiveTime::from_hms_opt(13, 0, 0)
+        );
+        assert_eq!(
+            stocks
+                .get_cal()
+                .half_day_close(Calendar::from_ymd(2021, 11, 24)),
+            None
+        );
+        // New Year's Eve closes early for bonds only, unless New Year's Day is moved
+        let new_years_eve = Calendar::from_ymd(2024, 12, 31);
+        assert_eq!(
+            bonds.get_cal().half_day_close(new_years_eve),
+            NaiveTime::from_hms_opt(14, 0, 0)
+        );
+        assert_eq!(stocks.get_cal().half_day_close(new_years_eve), None);
+        assert_eq!(
+            bonds
+                .get_cal()
+                .half_day_close(Calendar::from_ymd(2022, 12, 30)),
+            None
+        );
+        // Columbus Day is a bond market holiday only
+        assert_eq!(
+            false,
+            bonds
+                .get_cal()
+                .is_business_day(Calendar::from_ymd(2021, 10, 11))
+        );
+        assert_eq!(
+            true,
+            stocks
+                .get_cal()
+                .is_business_day(Calendar::from_ymd(2021, 10, 11))
+        );
+    }
+
+    #[test]
+    fn test_holiday_status() {
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        sc.populate_cal(Some(2021), Some(2022));
+        let c = sc.get_cal();
+        let status = |y, m, d| c.holiday_status(Calendar::from_ymd(y, m, d));
+        assert_eq!(status(2021, 11, 25), HolidayStatus::Holiday);
+        assert_eq!(status(2021, 11, 26), HolidayStatus::HalfDay);
+        assert_eq!(status(2021, 11, 24), HolidayStatus::BusinessDay);
+        assert_eq!(status(2021, 11, 27), HolidayStatus::Weekend);
+        assert_eq!(status(2023, 3, 1), HolidayStatus::Uncovered);
+        assert_eq!(status(2020, 12, 31), HolidayStatus::Uncovered);
+        // unpopulated calendar doesn't silently report business days
+        let c = UsExchangeCalendar::with_default_range(false).get_cal();
+        assert_eq!(
+            c.holiday_status(Calendar::from_ymd(2021, 12, 24)),
+            HolidayStatus::Uncovered
+        );
+    }
+
+    #[test]
+    fn test_holiday_weekday_distribution() {
+        let cal = make_cal();
+        let distribution = cal.holiday_weekday_distribution(
+            Calendar::from_ymd(2021, 1, 1),
+            Calendar::from_ymd(2021, 12, 31),
+        );
+        // Christmas 2021 is observed on Friday Dec 24th
+        assert_eq!(distribution, [5, 0, 0, 1, 3, 0, 0]);
+        let distribution = cal.holiday_weekday_distribution(
+            Calendar::from_ymd(2021, 12, 31),
+            Calendar::from_ymd(2021, 1, 1),
+        );
+        assert_eq!(distribution, [0; 7]);
+    }
+
+    #[test]
+    fn test_singular_half_day() {
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        sc.add_holiday_rule(Holiday::SingularHalfDay(Calendar::from_ymd(2022, 3, 3)))
+            .populate_cal(Some(2022), Some(2022));
+        let c = sc.get_cal();
+        assert_eq!(true, c.is_half_holiday(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(false, c.is_holiday(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(true, c.is_business_day(Calendar::from_ymd(2022, 3, 3)));
+
+        let json = serde_json::to_string(&Holiday::SingularHalfDay(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(json.unwrap(), r#"{"SingularHalfDay":"2022-03-03"}"#);
+    }
+
+    #[test]
+    fn test_holidays_in_range() {
+        let cal = make_cal();
+        let holidays: Vec<NaiveDate> = cal
+            .holidays_in_range(
+                Calendar::from_ymd(2021, 11, 1),
+                Calendar::from_ymd(2021, 12, 31),
+            )
+            .collect();
+        assert_eq!(
+            holidays,
+            vec![
+                Calendar::from_ymd(2021, 11, 25),
+                Calendar::from_ymd(2021, 12, 24)
+            ]
+        );
+        let halfdays: Vec<NaiveDate> = cal
+            .half_holidays_in_range(
+                Calendar::from_ymd(2021, 11, 1),
+                Calendar::from_ymd(2021, 12, 31),
+            )
+            .collect();
+        assert_eq!(halfdays, vec![Calendar::from_ymd(2021, 11, 26)]);
+        let date = Calendar::from_ymd(2021, 11, 25);
+        assert_eq!(cal.holidays_in_range(date, date).count(), 1);
+        assert_eq!(
+            cal.holidays_in_range(date, date.pred_opt().unwrap())
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_shift() {
+        let cal = make_cal();
+        let shifted = cal.shift(1);
+        // Thanksgiving and the early close on the day after
+        assert_eq!(false, shifted.is_holiday(Calendar::from_ymd(2021, 11, 25)));
+        assert_eq!(true, shifted.is_holiday(Calendar::from_ymd(2021, 11, 26)));
+        assert_eq!(
+            true,
+            shifted.is_half_holiday(Calendar::from_ymd(2021, 11, 27))
+        );
+        assert_eq!(cal.weekdays(), shifted.weekdays());
+        assert_eq!(cal.covered_range(), shifted.covered_range());
+        assert_eq!(cal, shifted.shift(-1));
+
+        let holidays = vec![Holiday::SingularDay(NaiveDate::MAX)];
+        let cal = Calendar::calc_calendar(&holidays, NaiveDate::MAX.year(), NaiveDate::MAX.year());
+        assert_eq!(
+            cal.shift(1)
+                .holidays_in_range(NaiveDate::MIN, NaiveDate::MAX)
+                .count(),
+            0
+        );
+        assert_eq!(
+            cal.shift(i64::MAX)
+                .holidays_in_range(NaiveDate::MIN, NaiveDate::MAX)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_business_day_of_quarter() {
+        let cal = make_cal();
+        // March 31st 2019 is a Sunday
+        assert_eq!(
+            cal.last_business_day_of_quarter(2019, 1),
+            Some(Calendar::from_ymd(2019, 3, 29))
+        );
+        // March 31st 2024 is Easter Sunday
+        assert_eq!(
+            cal.last_business_day_of_quarter(2024, 1),
+            Some(Calendar::from_ymd(2024, 3, 28))
+        );
+        assert_eq!(
+            cal.last_business_day_of_quarter(2021, 4),
+            Some(Calendar::from_ymd(2021, 12, 31))
+        );
+        assert_eq!(
+            cal.first_business_day_of_quarter(2022, 1),
+            Some(Calendar::from_ymd(2022, 1, 3))
+        );
+        assert_eq!(
+            cal.first_business_day_of_quarter(2022, 3),
+            Some(Calendar::from_ymd(2022, 7, 1))
+        );
+        assert_eq!(cal.first_business_day_of_quarter(2022, 5), None);
+        assert_eq!(cal.last_business_day_of_quarter(2022, 0), None);
+    }
+
+    #[test]
+    fn test_half_weekday() {
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::HalfWeekDay(Weekday::Sun),
+            Holiday::HalfWeekDay(Weekday::Sat),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        let sunday = Calendar::from_ymd(2022, 3, 6);
+        assert_eq!(true, cal.is_business_day(sunday));
+        assert_eq!(true, cal.is_half_holiday(sunday));
+        assert_eq!(cal.holiday_status(sunday), HolidayStatus::HalfDay);
+        assert_eq!(cal.next_biz_day(Calendar::from_ymd(2022, 3, 4)), sunday);
+        // a full weekend day takes precedence
+        let saturday = Calendar::from_ymd(2022, 3, 5);
+        assert_eq!(false, cal.is_half_holiday(saturday));
+        assert_eq!(cal.half_weekdays(), vec![Weekday::Sun]);
+    }
+
+    #[test]
+    fn test_days_in_year_and_quarter() {
+        assert_eq!(days_in_year(2000), 366);
+        assert_eq!(days_in_year(1900), 365);
+        assert_eq!(days_in_year(2024), 366);
+        assert_eq!(days_in_year(2023), 365);
+        assert_eq!(days_in_quarter(2000, 1), Some(91));
+        assert_eq!(days_in_quarter(1900, 1), Some(90));
+        assert_eq!(days_in_quarter(2024, 2), Some(91));
+        assert_eq!(days_in_quarter(2024, 3), Some(92));
+        assert_eq!(days_in_quarter(2024, 4), Some(92));
+        assert_eq!(
+            (1..=4)
+                .map(|quarter| days_in_quarter(2024, quarter))
+                .sum::<Option<u32>>(),
+            Some(days_in_year(2024))
+        );
+        assert_eq!(days_in_quarter(2024, 0), None);
+        assert_eq!(days_in_quarter(2024, 5), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let cal = make_cal();
+        assert_eq!(cal.fingerprint(), cal.clone().fingerprint());
+        assert_eq!(cal.fingerprint(), make_cal().fingerprint());
+        assert_ne!(cal.fingerprint(), cal.shift(1).fingerprint());
+
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(cal.fingerprint(), 0xba4c_4146_be7f_efd7);
+    }
+
+    #[test]
+    fn test_only_leap_years() {
+        let holiday = |only_leap_years| Holiday::MovableYearlyDay {
+            month: 3,
+            day: 1,
+            first: None,
+            last: None,
+            half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years,
+            observance: None,
+            name: None,
+        };
+        let cal = Calendar::calc_calendar(&[holiday(Some(true))], 2023, 2024);
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2023, 3, 1)));
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2024, 3, 1)));
+        let cal = Calendar::calc_calendar(&[holiday(Some(false))], 2023, 2024);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2023, 3, 1)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2024, 3, 1)));
+        let cal = Calendar::calc_calendar(&[holiday(None)], 2023, 2024);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2023, 3, 1)));
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2024, 3, 1)));
+    }
+
+    #[test]
+    fn test_checked_add_business_days() {
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        sc.populate_cal(Some(2021), Some(2021));
+        let c = sc.get_cal();
+        let date = Calendar::from_ymd(2021, 4, 1);
+        assert_eq!(c.checked_add_business_days(date, 0), Some(date));
+        assert_eq!(
+            c.checked_add_business_days(date, 1),
+            Some(Calendar::from_ymd(2021, 4, 5))
+        );
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2021, 4, 5), -2),
+            Some(Calendar::from_ymd(2021, 3, 31))
+        );
+        // stepping out of the covered range
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2021, 1, 5), -2),
+            None
+        );
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2021, 12, 30), 2),
+            None
+        );
+        assert_eq!(c.checked_add_business_days(date, i64::MIN), None);
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2022, 1, 3), 1),
+            None
+        );
+
+        // calendars without covered range are unbounded
+        let c = Calendar::from_weekmask("1111100", &[Calendar::from_ymd(2024, 1, 1)]).unwrap();
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2023, 12, 29), 1),
+            Some(Calendar::from_ymd(2024, 1, 2))
+        );
+        assert_eq!(
+            c.checked_add_business_days(Calendar::from_ymd(2024, 1, 2), -1),
+            Some(Calendar::from_ymd(2023, 12, 29))
+        );
+        let c = Calendar::from_weekmask("0000000", &[]).unwrap();
+        assert_eq!(c.checked_add_business_days(date, 1), None);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut cal = make_cal();
+        let closure = Calendar::from_ymd(2022, 3, 3);
+        let early_close = Calendar::from_ymd(2022, 4, 15);
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        assert_eq!(true, cal.is_business_day(closure));
+        assert_eq!(false, cal.is_business_day(early_close));
+        cal.add_override_closure(closure)
+            .add_override_early_close(early_close, noon);
+        assert_eq!(false, cal.is_business_day(closure));
+        assert_eq!(true, cal.is_business_day(early_close));
+        assert_eq!(cal.half_day_close(early_close), Some(noon));
+        // closing an early close day removes the half-day
+        let day_after_thanksgiving = Calendar::from_ymd(2021, 11, 26);
+        cal.add_override_closure(day_after_thanksgiving);
+        assert_eq!(false, cal.is_half_holiday(day_after_thanksgiving));
+        assert_eq!(false, cal.is_business_day(day_after_thanksgiving));
+    }
+
+    #[test]
+    fn test_trading_days_remaining() {
+        let cal = make_cal();
+        assert_eq!(
+            cal.trading_days_remaining(Calendar::from_ymd(2021, 12, 28)),
+            3
+        );
+        // Christmas observed on Friday Dec 24th
+        assert_eq!(
+            cal.trading_days_remaining(Calendar::from_ymd(2021, 12, 23)),
+            5
+        );
+        assert_eq!(
+            cal.trading_days_remaining(Calendar::from_ymd(2021, 12, 31)),
+            0
+        );
+        assert_eq!(
+            cal.trading_days_remaining(Calendar::from_ymd(2020, 12, 31)),
+            0
+        );
+        assert_eq!(
+            cal.trading_days_remaining(Calendar::from_ymd(2021, 1, 1)),
+            252
+        );
+    }
+
+    #[test]
+    fn test_default_range() {
+        let c = UsExchangeCalendar::with_default_range(true).get_cal();
+        let today = Local::now().date_naive();
+        assert!(c.is_covered(today));
+        assert!(c.is_covered(Calendar::from_ymd(
+            today.year() + DEFAULT_YEARS_AHEAD,
+            12,
+            31
+        )));
+        let (first, last) = c.covered_range().unwrap();
+        assert_eq!(first, Calendar::from_ymd(DEFAULT_START_YEAR, 1, 1));
+        assert!(last >= Calendar::from_ymd(DEFAULT_END_YEAR, 12, 31));
+    }
+
+    #[test]
+    fn test_filter_business_days() {
+        let cal = make_cal();
+        let days: Vec<NaiveDate> = Calendar::from_ymd(2022, 3, 5)
+            .iter_days()
+            .take(7)
+            .filter_business_days(&cal)
+            .collect();
+        assert_eq!(days.len(), 5);
+        assert_eq!(days[0], Calendar::from_ymd(2022, 3, 7));
+        // week of Good Friday
+        let days = Calendar::from_ymd(2022, 4, 11)
+            .iter_days()
+            .take(7)
+            .filter_business_days(&cal);
+        assert_eq!(days.count(), 4);
+    }
+
+    #[test]
+    fn test_month_weekday_offset() {
+        // US Election Day, the Tuesday after the first Monday in November
+        let holidays = vec![Holiday::MonthWeekday {
+            month: 11,
+            weekday: Weekday::Mon,
+            nth: NthWeek::First,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 1,
+            name: None,
+        }];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2024);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2024, 11, 5)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2024, 11, 4)));
+        // November 1st 2022 is a Tuesday
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 11, 8)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2022, 11, 1)));
+    }
+
+    #[test]
+    fn test_closest_business_day() {
+        let cal = make_cal();
+        let date = Calendar::from_ymd(2022, 3, 2);
+        assert_eq!(
+            cal.closest_business_day(date, RollConvention::Following),
+            date
+        );
+        // Saturday and Sunday
+        assert_eq!(
+            cal.closest_business_day(Calendar::from_ymd(2022, 3, 5), RollConvention::Following),
+            Calendar::from_ymd(2022, 3, 4)
+        );
+        assert_eq!(
+            cal.closest_business_day(Calendar::from_ymd(2022, 3, 6), RollConvention::Preceding),
+            Calendar::from_ymd(2022, 3, 7)
+        );
+        // Good Friday, Thursday is one day away and Monday three
+        assert_eq!(
+            cal.closest_business_day(Calendar::from_ymd(2022, 4, 15), RollConvention::Following),
+            Calendar::from_ymd(2022, 4, 14)
+        );
+        // Thanksgiving, Wednesday and Friday are equally close
+        let thanksgiving = Calendar::from_ymd(2021, 11, 25);
+        assert_eq!(
+            cal.closest_business_day(thanksgiving, RollConvention::Following),
+            Calendar::from_ymd(2021, 11, 26)
+        );
+        assert_eq!(
+            cal.closest_business_day(thanksgiving, RollConvention::Preceding),
+            Calendar::from_ymd(2021, 11, 24)
+        );
+    }
+
+    #[test]
+    fn test_easter_offset_adjust() {
+        // Easter Saturday, rolled to Monday or to Good Friday
+        let rule = |adjust| Holiday::EasterOffset {
+            offset: -1,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust,
+            half_check: None,
+            name: None,
+        };
+        let mut holidays = vec![
+            rule(Some(RollConvention::Following)),
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 4, 18)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2022, 4, 16)));
+        holidays[0] = rule(Some(RollConvention::Preceding));
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 4, 15)));
+        holidays[0] = rule(None);
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 4, 16)));
+        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2022, 4, 18)));
+        // no business day to roll to
+        holidays[0] = rule(Some(RollConvention::Following));
+        holidays.extend(
+            [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]
+            .map(Holiday::WeekDay),
+        );
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 4, 16)));
+    }
+
+    #[test]
+    fn test_count_index() {
+        let mut sc = UsExchangeCalendar::with_default_range(false);
+        sc.populate_cal(Some(2020), Some(2022));
+        let plain = sc.get_cal();
+        let mut indexed = plain.clone();
+        indexed.build_count_index();
+        assert_eq!(plain, indexed);
+        let spans = [
+            ((2020, 1, 1), (2022, 12, 31)),
+            ((2021, 4, 1), (2021, 4, 6)),
+            ((2021, 4, 2), (2021, 4, 2)),
+            ((2022, 12, 30), (2023, 1, 5)),
+            ((2019, 12, 30), (2020, 1, 3)),
+        ];
+        for ((y1, m1, d1), (y2, m2, d2)) in spans {
+            let start = Calendar::from_ymd(y1, m1, d1);
+            let end = Calendar::from_ymd(y2, m2, d2);
+            for bounds in [Bounds::Inclusive, Bounds::Exclusive, Bounds::IncludeStart] {
+                assert_eq!(
+                    plain.business_days_between(start, end, bounds),
+                    indexed.business_days_between(start, end, bounds)
+                );
+            }
+        }
+        assert_eq!(
+            indexed.business_days_between(
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2021, 12, 31),
+                Bounds::Inclusive
+            ),
+            252
+        );
+        // overrides discard the index
+        let date = Calendar::from_ymd(2021, 3, 3);
+        indexed.add_override_closure(date);
+        assert_eq!(
+            indexed.business_days_between(date, date, Bounds::Inclusive),
+            0
+        );
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn test_warn_rule_without_dates() {
+        use std::sync::Mutex;
+
+        static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        struct Logger;
+        impl log::Log for Logger {
+            fn enabled(&self, metadata: &log::Metadata) -> bool {
+                metadata.level() <= log::Level::Warn
+            }
+            fn log(&self, record: &log::Record) {
+                WARNINGS.lock().unwrap().push(record.args().to_string());
+            }
+            fn flush(&self) {}
+        }
+        log::set_logger(&Logger).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)),
+            Holiday::EasterOffset {
+                offset: -2,
+                first: None,
+                last: Some(2010),
+                except: vec![],
+                only_leap_years: None,
+                adjust: None,
+                half_check: None,
+                name: None,
+            },
+        ];
+        Calendar::calc_calendar(&holidays, 2022, 2022);
+        let warnings = WARNINGS.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("holiday rule EasterOffset"));
+        assert!(warnings[0].ends_with("produces no dates from 2022 to 2022"));
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let uscal = UsExchangeCalendar::with_rolling_window(1, 5);
+        let cal = uscal.get_cal();
+        let year = Local::now().year();
+        assert!(cal.is_covered(Local::now().date_naive()));
+        assert_eq!(
+            cal.covered_range(),
+            Some((
+                Calendar::from_ymd(year - 1, 1, 1),
+                Calendar::from_ymd(year + 5, 12, 31)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_business_day_mask() {
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        let start = Calendar::from_ymd(2021, 12, 1);
+        let end = Calendar::from_ymd(2022, 1, 31);
+        let mask = cal.business_day_mask(start, end);
+        assert_eq!(mask.len(), 62);
+        for (i, is_biz) in mask.iter().enumerate() {
+            let date = start + Duration::days(i as i64);
+            assert_eq!(*is_biz, cal.is_business_day(date), "{}", date);
+        }
+        assert!(cal.business_day_mask(end, start).is_empty());
+
+        let mut cal = cal;
+        cal.set_inception(Some(Calendar::from_ymd(2022, 1, 10)));
+        let mask = cal.business_day_mask(start, end);
+        for (i, is_biz) in mask.iter().enumerate() {
+            let date = start + Duration::days(i as i64);
+            assert_eq!(*is_biz, cal.is_business_day(date), "{}", date);
+        }
+        assert_eq!(mask.iter().filter(|is_biz| **is_biz).count(), 15);
+    }
+
+    #[test]
+    fn test_monday_if_weekend_observance() {
+        // Jan 1st 2022 and Christmas 2021 are on Saturday, Christmas 2022 on Sunday
+        let holiday = |month, day| Holiday::MovableYearlyDay {
+            month,
+            day,
+            first: None,
+            last: None,
+            half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: Some(Observance::MondayIfWeekend),
+            name: None,
+        };
+        let cal = Calendar::calc_calendar(&[holiday(1, 1), holiday(12, 25)], 2021, 2022);
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 1, 3)));
+        assert!(!cal.is_holiday(Calendar::from_ymd(2021, 12, 31)));
+        assert!(cal.is_holiday(Calendar::from_ymd(2021, 12, 27)));
+        assert!(!cal.is_holiday(Calendar::from_ymd(2021, 12, 24)));
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 12, 26)));
+    }
+
+    #[test]
+    fn test_next_business_day_on_or_after() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.populate_cal(Some(2021), Some(2022));
+        let cal = uscal.get_cal();
+        let on_or_after = |y, m, d| cal.next_business_day_on_or_after(Calendar::from_ymd(y, m, d));
+        assert_eq!(
+            on_or_after(2022, 12, 29),
+            Some(Calendar::from_ymd(2022, 12, 29))
+        );
+        assert_eq!(
+            on_or_after(2022, 1, 1),
+            Some(Calendar::from_ymd(2022, 1, 3))
+        );
+        // Dec 30th 2022 is the last business day of the populated range
+        assert_eq!(
+            on_or_after(2022, 12, 30),
+            Some(Calendar::from_ymd(2022, 12, 30))
+        );
+        assert_eq!(on_or_after(2022, 12, 31), None);
+        assert_eq!(on_or_after(2023, 1, 3), None);
+
+        // no covered range to leave
+        let cal = Calendar::from_weekmask("1111100", &[Calendar::from_ymd(2024, 1, 1)]).unwrap();
+        assert_eq!(
+            cal.next_business_day_on_or_after(Calendar::from_ymd(2023, 12, 30)),
+            Some(Calendar::from_ymd(2024, 1, 2))
+        );
+    }
+
+    #[test]
+    fn test_memorial_and_labor_day() {
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        let memorial_days = [
+            (2020, 25),
+            (2021, 31),
+            (2022, 30),
+            (2023, 29),
+            (2024, 27),
+            (2025, 26),
+            (2026, 25),
+            (2027, 31),
+            (2028, 29),
+            (2029, 28),
+            (2030, 27),
+            (2031, 26),
+            (2032, 31),
+            (2033, 30),
+            (2034, 29),
+            (2035, 28),
+        ];
+        for (year, day) in memorial_days {
+            let date = Calendar::from_ymd(year, 5, day);
+            assert!(cal.is_holiday(date), "{}", date);
+            assert!(!cal.is_holiday(date - Duration::days(7)), "{}", date);
+        }
+        for year in 2020..=2035 {
+            let labor_day = nth_weekday_of_month(year, 9, Weekday::Mon, &NthWeek::First);
+            assert!(labor_day.day() <= 7);
+            assert!(cal.is_holiday(labor_day), "{}", labor_day);
+        }
+    }
+
+    #[test]
+    fn test_nth_weekday_stays_in_month() {
+        let weekdays = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ];
+        for year in 2020..=2035 {
+            for month in 1..=12 {
+                for weekday in weekdays {
+                    let last = nth_weekday_of_month(year, month, weekday, &NthWeek::Last);
+                    assert_eq!(last.weekday(), weekday);
+                    assert_eq!(last.month(), month);
+                    assert_ne!((last + Duration::days(7)).month(), month);
+                    let fourth = nth_weekday_of_month(year, month, weekday, &NthWeek::Fourth);
+                    assert_eq!(fourth.weekday(), weekday);
+                    assert_eq!(fourth.month(), month);
+                    assert_eq!((fourth - Duration::days(21)).month(), month);
+                    assert_ne!((fourth - Duration::days(28)).month(), month);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_explicit_half_days() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.populate_cal(Some(2022), Some(2023));
+        let dates = vec![
+            Calendar::from_ymd(2022, 12, 30),
+            Calendar::from_ymd(2023, 6, 16),
+        ];
+        uscal.with_explicit_half_days(dates.clone());
+        let cal = uscal.get_cal();
+        for date in dates {
+            assert!(cal.is_half_holiday(date), "{}", date);
+            assert!(cal.is_business_day(date), "{}", date);
+        }
+        assert_eq!(
+            cal.covered_range(),
+            Some((
+                Calendar::from_ymd(2022, 1, 1),
+                Calendar::from_ymd(2023, 12, 31)
+            ))
+        );
+        // the default half-days are kept
+        assert!(cal.is_half_holiday(Calendar::from_ymd(2022, 11, 25)));
+    }
+
+    #[test]
+    fn test_us_exchange_calendar_serde() {
+        let mut uscal = UsExchangeCalendar::sifma(false);
+        uscal.add_holiday_rule(Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)));
+        uscal.populate_cal(Some(2021), Some(2023));
+        let json = serde_json::to_string(&uscal).unwrap();
+        let restored: UsExchangeCalendar = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.holiday_rules, uscal.holiday_rules);
+        assert_eq!(restored.get_cal(), uscal.get_cal());
+        assert!(restored
+            .get_cal()
+            .is_holiday(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(
+            restored.session(Calendar::from_ymd(2022, 11, 25)),
+            uscal.session(Calendar::from_ymd(2022, 11, 25))
+        );
+
+        let unpopulated = UsExchangeCalendar::with_default_range(false);
+        let json = serde_json::to_string(&unpopulated).unwrap();
+        assert!(!json.contains("years"));
+        let restored: UsExchangeCalendar = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_cal().covered_range(), None);
+    }
+
+    #[test]
+    fn test_closure_reasons() {
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::SingularDay(Calendar::from_ymd(2022, 3, 5)),
+            Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)),
+            Holiday::EasterOffset {
+                offset: 0,
+                first: None,
+                last: None,
+                except: vec![],
+                only_leap_years: None,
+                adjust: None,
+                half_check: None,
+                name: Some("Easter Sunday".to_string()),
+            },
+        ];
+        let mut cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        cal.add_override_closure(Calendar::from_ymd(2022, 3, 8));
+        let reasons = |d| cal.closure_reasons(Calendar::from_ymd(2022, 3, d));
+        let holiday = |name: Option<&str>, source| ClosureReason::Holiday {
+            name: name.map(str::to_string),
+            source,
+        };
+        assert_eq!(
+            reasons(5),
+            vec![ClosureReason::Weekend(Weekday::Sat), holiday(None, Some(2))]
+        );
+        assert_eq!(reasons(6), vec![ClosureReason::Weekend(Weekday::Sun)]);
+        assert_eq!(reasons(3), vec![holiday(None, Some(3))]);
+        assert_eq!(reasons(8), vec![holiday(None, None)]);
+        assert!(reasons(7).is_empty());
+        assert_eq!(
+            cal.closure_reasons(Calendar::from_ymd(2022, 4, 17)),
+            vec![
+                ClosureReason::Weekend(Weekday::Sun),
+                holiday(Some("Easter Sunday"), Some(4))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_holiday_sources() {
+        let rules = default_us_holiday_rules();
+        let cal = Calendar::calc_calendar(&rules, 2021, 2023);
+        let new_year = cal.holiday_source(Calendar::from_ymd(2023, 1, 2)).unwrap();
+        assert!(matches!(
+            rules[new_year],
+            Holiday::MovableYearlyDay {
+                month: 1,
+                day: 1,
+                ..
+            }
+        ));
+        // Jan 1st 2022 is on Saturday, no holiday
+        assert_eq!(
+            cal.holidays_from_rule(new_year).collect::<Vec<_>>(),
+            vec![
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2023, 1, 2)
+            ]
+        );
+        assert_eq!(cal.holiday_source(Calendar::from_ymd(2023, 1, 3)), None);
+        assert_eq!(
+            cal.holiday_sources().len(),
+            cal.holidays_in_range(
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2023, 12, 31)
+            )
+            .count()
+        );
+    }
+
+    #[test]
+    fn test_business_day_in_range() {
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        // Christmas 2022 and New Year's Day 2023 are observed on Mondays
+        let span = Calendar::from_ymd(2022, 12, 24)..=Calendar::from_ymd(2023, 1, 2);
+        assert_eq!(
+            cal.first_business_day_in(span.clone()),
+            Some(Calendar::from_ymd(2022, 12, 27))
+        );
+        assert_eq!(
+            cal.last_business_day_in(span),
+            Some(Calendar::from_ymd(2022, 12, 30))
+        );
+        let weekend = Calendar::from_ymd(2022, 12, 31)..=Calendar::from_ymd(2023, 1, 2);
+        assert_eq!(cal.first_business_day_in(weekend.clone()), None);
+        assert_eq!(cal.last_business_day_in(weekend), None);
+    }
+
+    #[test]
+    fn test_with_cutover() {
+        let before = UsExchangeCalendar::sifma(false)
+            .populate_cal(Some(2021), Some(2022))
+            .get_cal();
+        let after = UsExchangeCalendar::with_default_range(false)
+            .populate_cal(Some(2021), Some(2022))
+            .get_cal();
+        let cutover = Calendar::from_ymd(2022, 1, 1);
+        let cal = Calendar::with_cutover(&before, &after, cutover);
+        // Columbus Day is a SIFMA holiday only, Juneteenth only an exchange holiday from 2022
+        assert!(cal.is_holiday(Calendar::from_ymd(2021, 10, 11)));
+        assert!(!cal.is_holiday(Calendar::from_ymd(2022, 10, 10)));
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 6, 20)));
+        // SIFMA early closes are at 2:00 PM, exchange ones at 1:00 PM
+        assert_eq!(
+            cal.half_day_close(Calendar::from_ymd(2021, 11, 26)),
+            NaiveTime::from_hms_opt(14, 0, 0)
+        );
+        assert_eq!(
+            cal.half_day_close(Calendar::from_ymd(2022, 11, 25)),
+            NaiveTime::from_hms_opt(13, 0, 0)
+        );
+        assert_eq!(cal.covered_range(), after.covered_range());
+
+        let crypto = Calendar::calc_calendar(&[Holiday::WeekDay(Weekday::Sun)], 2022, 2022);
+        let cal = Calendar::with_cutover(&after, &crypto, Calendar::from_ymd(2022, 7, 1));
+        assert!(!cal.is_business_day(Calendar::from_ymd(2022, 6, 25)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2022, 7, 2)));
+        assert!(!cal.is_business_day(Calendar::from_ymd(2022, 7, 3)));
+    }
+
+    #[test]
+    fn test_holiday_name() {
+        let uscal = UsExchangeCalendar::from_rules(
+            vec![
+                Holiday::WeekDay(Weekday::Fri),
+                Holiday::WeekDay(Weekday::Sat),
+                Holiday::EasterOffset {
+                    offset: 50,
+                    first: None,
+                    last: None,
+                    except: vec![],
+                    only_leap_years: None,
+                    adjust: None,
+                    half_check: None,
+                    name: Some("Shavuot".to_string()),
+                },
+                Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)),
+            ],
+            false,
+        )
+        .populate_cal(Some(2022), Some(2022))
+        .get_cal();
+        assert_eq!(
+            uscal.holiday_name(Calendar::from_ymd(2022, 6, 6)),
+            Some("Shavuot")
+        );
+        assert!(uscal.is_holiday(Calendar::from_ymd(2022, 3, 3)));
+        assert_eq!(uscal.holiday_name(Calendar::from_ymd(2022, 3, 3)), None);
+
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        assert_eq!(
+            cal.holiday_name(Calendar::from_ymd(2021, 12, 24)),
+            Some("Christmas Day")
+        );
+        assert_eq!(cal.holiday_name(Calendar::from_ymd(2021, 12, 23)), None);
+    }
+
+    #[test]
+    fn test_trading_sessions() {
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        let sessions = cal.trading_sessions(
+            Calendar::from_ymd(2022, 11, 23),
+            Calendar::from_ymd(2022, 11, 28),
+        );
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(
+            sessions,
+            vec![
+                (Calendar::from_ymd(2022, 11, 23), time(16)),
+                (Calendar::from_ymd(2022, 11, 25), time(13)),
+                (Calendar::from_ymd(2022, 11, 28), time(16)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_easter_out_of_range() {
+        let rules = vec![Holiday::EasterOffset {
+            offset: 1,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: None,
+            half_check: None,
+            name: None,
+        }];
+        let mut uscal = UsExchangeCalendar::from_rules(rules.clone(), false);
+        assert!(matches!(
+            uscal.try_populate_cal(Some(1500), Some(1501)),
+            Err(Error::UnsupportedYear(1500))
+        ));
+        assert!(matches!(
+            Calendar::try_calc_calendar(&rules, 9999, 10000),
+            Err(Error::UnsupportedYear(10000))
+        ));
+        // Good Friday is a holiday from 2000 on only in the default rules
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        assert!(uscal.try_populate_cal(Some(1500), Some(1501)).is_ok());
+        // offsets moving the date past the representable dates
+        let rules = vec![Holiday::EasterOffset {
+            offset: 2_000_000_000,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: None,
+            half_check: None,
+            name: None,
+        }];
+        assert!(matches!(
+            Calendar::try_calc_calendar(&rules, 2000, 2002),
+            Err(Error::UnsupportedYear(2000))
+        ));
+        let rules = vec![Holiday::MonthWeekday {
+            month: 1,
+            weekday: Weekday::Mon,
+            nth: NthWeek::First,
+            first: None,
+            last: None,
+            half_check: None,
+            except: vec![],
+            only_leap_years: None,
+            offset_days: -2_000_000_000,
+            name: None,
+        }];
+        assert!(matches!(
+            Calendar::try_calc_calendar(&rules, 2000, 2002),
+            Err(Error::UnsupportedYear(2000))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let calc = |json: &str| {
+            let rules: Vec<Holiday> = serde_json::from_str(json).unwrap();
+            Calendar::try_calc_calendar(&rules, 2000, 2002)
+        };
+        for json in [
+            r#"[{"RecurringHalfDay":{"weekday":"Fri","months":[13],"close_time":"12:00:00"}}]"#,
+            r#"[{"MovableYearlyDay":{"month":13,"day":1,"first":null,"last":null,"half_check":null}}]"#,
+            r#"[{"MovableYearlyDay":{"month":2,"day":30,"first":null,"last":null,"half_check":null}}]"#,
+            r#"[{"MonthWeekday":{"month":0,"weekday":"Mon","nth":"First","first":null,"last":null,"half_check":null}}]"#,
+        ] {
+            assert!(matches!(calc(json), Err(Error::InvalidRule(_))), "{}", json);
+        }
+        assert!(matches!(
+            calc(r#"[{"EasterOffset":{"offset":2000000000,"first":null,"last":null}}]"#),
+            Err(Error::UnsupportedYear(2000))
+        ));
+        let cal = calc(
+            r#"[{"WeekDay":"Mon"},{"WeekDay":"Tue"},{"WeekDay":"Wed"},{"WeekDay":"Thu"},
+            {"WeekDay":"Fri"},{"WeekDay":"Sat"},{"WeekDay":"Sun"},
+            {"EasterOffset":{"offset":0,"first":null,"last":null,"adjust":"Following"}}]"#,
+        )
+        .unwrap();
+        assert!(cal.is_holiday(Calendar::from_ymd(2000, 4, 23)));
+        // leap day holidays are skipped in other years
+        assert!(calc(
+            r#"[{"MovableYearlyDay":{"month":2,"day":29,"first":null,"last":null,"half_check":null}}]"#,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_is_business_day_of() {
+        let cal = make_cal();
+        let at = Calendar::from_ymd(2022, 3, 3)
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        assert_eq!(cal.is_business_day_of(&at), cal.is_business_day(at.date()));
+        let at = Calendar::from_ymd(2022, 3, 4)
+            .and_hms_opt(23, 59, 0)
+            .unwrap();
+        assert!(cal.is_business_day_of(&at));
+        let utc = FixedOffset::east_opt(0).unwrap().from_utc_datetime(&at);
+        assert!(cal.is_business_day_of(&utc));
+        assert!(!cal.is_business_day_of(&(utc + Duration::days(1))));
+    }
+
+    #[test]
+    fn test_holidays_for_year() {
+        let cal = make_cal();
+        let dates = |days: &[(u32, u32)]| -> Vec<NaiveDate> {
+            days.iter()
+                .map(|(month, day)| Calendar::from_ymd(2021, *month, *day))
+                .collect()
+        };
+        assert_eq!(
+            cal.holidays_for_year(2021),
+            dates(&[
+                (1, 1),
+                (1, 18),
+                (2, 15),
+                (4, 2),
+                (5, 31),
+                (7, 5),
+                (9, 6),
+                (11, 25),
+                (12, 24)
+            ])
+        );
+        assert_eq!(cal.half_holidays_for_year(2021), dates(&[(11, 26)]));
+    }
+
+    #[test]
+    fn test_nyse_special_closures() {
+        let nyse = UsExchangeCalendar::nyse(true).get_cal();
+        let default = make_cal();
+        for day in 11..=14 {
+            let date = Calendar::from_ymd(2001, 9, day);
+            assert!(nyse.is_holiday(date), "{}", date);
+            assert!(default.is_business_day(date), "{}", date);
+        }
+        assert!(nyse.is_business_day(Calendar::from_ymd(2001, 9, 17)));
+        assert!(nyse.is_holiday(Calendar::from_ymd(2012, 10, 30)));
+        assert!(default.is_business_day(Calendar::from_ymd(2012, 10, 30)));
+    }
+
+    #[test]
+    fn test_holiday_takes_precedence_over_half_day() {
+        let date = Calendar::from_ymd(2022, 3, 3);
+        let holidays = vec![
+            Holiday::SingularHalfDay(date),
+            Holiday::SingularDay(date),
+            Holiday::HalfWeekDay(Weekday::Sun),
+            Holiday::SingularDay(Calendar::from_ymd(2022, 3, 6)),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
+        assert!(cal.is_holiday(date));
+        assert!(!cal.is_half_holiday(date));
+        assert_eq!(cal.half_day_close(date), None);
+        assert_eq!(cal.holiday_status(date), HolidayStatus::Holiday);
+        assert!(!cal.is_half_holiday(Calendar::from_ymd(2022, 3, 6)));
+        assert!(cal.is_half_holiday(Calendar::from_ymd(2022, 3, 13)));
+    }
+
+    #[test]
+    fn test_trading_calendar_trait() {
+        // every day but Wednesday is a trading day, Tuesdays are half-days
+        struct NoWednesdays;
+        impl TradingCalendar for NoWednesdays {
+            fn is_business_day(&self, date: NaiveDate) -> bool {
+                date.weekday() != Weekday::Wed
+            }
+            fn is_holiday(&self, _date: NaiveDate) -> bool {
+                false
+            }
+            fn is_half_holiday(&self, date: NaiveDate) -> bool {
+                date.weekday() == Weekday::Tue
+            }
+        }
+        fn count_full_days<C: TradingCalendar>(cal: &C, start: NaiveDate, days: usize) -> usize {
+            start
+                .iter_days()
+                .take(days)
+                .filter(|date| cal.is_business_day(*date) && !cal.is_half_holiday(*date))
+                .count()
+        }
+
+        let start = Calendar::from_ymd(2022, 11, 21);
+        assert_eq!(count_full_days(&NoWednesdays, start, 7), 5);
+        assert_eq!(
+            NoWednesdays.next_biz_day(Calendar::from_ymd(2022, 11, 22)),
+            Calendar::from_ymd(2022, 11, 24)
+        );
+        let uscal = UsExchangeCalendar::with_default_range(true);
+        assert_eq!(count_full_days(&uscal, start, 7), 3);
+        assert_eq!(count_full_days(&uscal.get_cal(), start, 7), 3);
+        assert_eq!(
+            TradingCalendar::prev_biz_day(&uscal, Calendar::from_ymd(2022, 11, 28)),
+            Calendar::from_ymd(2022, 11, 25)
+        );
+    }
+
+    #[test]
+    fn test_christmas_observance() {
+        let cal = make_cal();
+        // (year, observed holiday, early close)
+        let christmas = [
+            (2020, 25, Some(24)), // Friday
+            (2021, 24, None),     // Saturday
+            (2022, 26, None),     // Sunday
+            (2023, 25, None),     // Monday
+            (2024, 25, Some(24)), // Wednesday
+            (2025, 25, Some(24)), // Thursday
+            (2026, 25, Some(24)), // Friday
+            (2027, 24, None),     // Saturday
+            (2028, 25, None),     // Monday
+            (2029, 25, Some(24)), // Tuesday
+            (2030, 25, Some(24)), // Wednesday
+        ];
+        for (year, observed, early_close) in christmas {
+            let days = |days: &[u32]| -> Vec<NaiveDate> {
+                days.iter()
+                    .map(|day| Calendar::from_ymd(year, 12, *day))
+                    .collect()
+            };
+            let holidays = cal
+                .holidays_in_range(
+                    Calendar::from_ymd(year, 12, 20),
+                    Calendar::from_ymd(year, 12, 31),
+                )
+                .collect::<Vec<_>>();
+            assert_eq!(holidays, days(&[observed]), "{}", year);
+            let halfdays = cal
+                .half_holidays_in_range(
+                    Calendar::from_ymd(year, 12, 20),
+                    Calendar::from_ymd(year, 12, 31),
+                )
+                .collect::<Vec<_>>();
+            assert_eq!(halfdays, days(early_close.as_slice()), "{}", year);
+        }
+    }
+
+    #[test]
+    fn test_build_calendar() {
+        let cal =
+            UsExchangeCalendar::with_default_range(false).build_calendar(Some(2021), Some(2022));
+        assert_eq!(
+            cal.covered_range(),
+            Some((
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2022, 12, 31)
+            ))
+        );
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 6, 20)));
+        assert_eq!(
+            cal,
+            UsExchangeCalendar::with_default_range(false)
+                .populate_cal(Some(2021), Some(2022))
+                .get_cal()
+        );
+    }
+
+    #[test]
+    fn test_recurring_half_day() {
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::RecurringHalfDay {
+                weekday: Weekday::Fri,
+                months: vec![7, 8],
+                close_time: noon,
+                first: None,
+                last: Some(2022),
+                name: Some("Summer Fridays".to_string()),
+            },
+            Holiday::SingularDay(Calendar::from_ymd(2022, 8, 5)),
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 2022, 2023);
+        let summer_fridays = cal
+            .half_holidays_in_range(
+                Calendar::from_ymd(2022, 1, 1),
+                Calendar::from_ymd(2022, 12, 31),
+            )
+            .map(|date| date.day())
+            .collect::<Vec<_>>();
+        assert_eq!(summer_fridays, vec![1, 8, 15, 22, 29, 12, 19, 26]);
+        assert_eq!(
+            cal.half_day_close(Calendar::from_ymd(2022, 7, 29)),
+            Some(noon)
+        );
+        assert_eq!(cal.half_day_close(Calendar::from_ymd(2022, 9, 2)), None);
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 8, 5)));
+        assert!(!cal.is_half_holiday(Calendar::from_ymd(2023, 7, 7)));
+        assert_eq!(holidays[2].name(), Some("Summer Fridays"));
+
+        // no early closes on weekend days
+        let saturdays = Holiday::RecurringHalfDay {
+            weekday: Weekday::Sat,
+            months: vec![7],
+            close_time: noon,
+            first: None,
+            last: None,
+            name: None,
+        };
+        let cal = Calendar::calc_calendar(&[holidays[0].clone(), saturdays.clone()], 2022, 2022);
+        assert!(!cal.is_half_holiday(Calendar::from_ymd(2022, 7, 2)));
+
+        let invalid = Holiday::RecurringHalfDay {
+            weekday: Weekday::Fri,
+            months: vec![13],
+            close_time: noon,
+            first: None,
+            last: None,
+            name: None,
+        };
+        assert!(matches!(
+            Calendar::try_calc_calendar(&[invalid], 2022, 2022),
+            Err(Error::InvalidRule(_))
+        ));
+    }
+
+    #[test]
+    fn test_business_day_predicate() {
+        let cal = make_cal();
+        let is_business_day = cal.business_day_predicate();
+        let dates = vec![
+            Calendar::from_ymd(2022, 7, 1),
+            Calendar::from_ymd(2022, 7, 2),
+            Calendar::from_ymd(2022, 7, 4),
+            Calendar::from_ymd(2022, 7, 5),
+        ];
+        let business_days: Vec<NaiveDate> = dates
+            .into_iter()
+            .filter(|date| is_business_day(*date))
+            .collect();
+        assert_eq!(
+            business_days,
+            vec![
+                Calendar::from_ymd(2022, 7, 1),
+                Calendar::from_ymd(2022, 7, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bounded_weekend_day() {
+        let holidays = vec![
+            Holiday::WeekDay(Weekday::Sun),
+            Holiday::BoundedWeekDay {
+                weekday: Weekday::Sat,
+                first: Some(1952),
+                last: None,
+            },
+        ];
+        let cal = Calendar::calc_calendar(&holidays, 1940, 1960);
+        let saturday_1940 = Calendar::from_ymd(1940, 3, 2);
+        assert!(cal.is_business_day(saturday_1940));
+        assert!(!cal.is_weekend(saturday_1940));
+        assert!(!cal.is_business_day(Calendar::from_ymd(1940, 3, 3)));
+        assert!(cal.is_business_day(Calendar::from_ymd(1951, 12, 29)));
+        assert!(cal.is_weekend(Calendar::from_ymd(1952, 1, 5)));
+        assert!(cal.is_weekend(Calendar::from_ymd(1960, 3, 5)));
+        assert!(!cal.is_business_day(Calendar::from_ymd(1960, 3, 5)));
+        assert_eq!(cal.weekdays(), vec![Weekday::Sat, Weekday::Sun]);
+
+        // an unbounded rule for the same day wins
+        let mut holidays = holidays;
+        holidays.push(Holiday::WeekDay(Weekday::Sat));
+        let cal = Calendar::calc_calendar(&holidays, 1940, 1960);
+        assert!(cal.is_weekend(saturday_1940));
+    }
+
+    #[test]
+    fn test_bounded_weekend_day_toggle() {
+        // Saturday becomes a trading day from 2011 on
+        let rules: Vec<Holiday> = serde_json::from_str(
+            r#"[{"WeekDay": "Sun"}, {"BoundedWeekDay": {"weekday": "Sat", "last": 2010}}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules[1],
+            Holiday::BoundedWeekDay {
+                weekday: Weekday::Sat,
+                first: None,
+                last: Some(2010),
+            }
+        );
+        let cal = Calendar::calc_calendar(&rules, 2009, 2012);
+        assert!(!cal.is_business_day(Calendar::from_ymd(2010, 12, 25)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2011, 1, 1)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2012, 12, 29)));
+        assert!(!cal.is_business_day(Calendar::from_ymd(2012, 12, 30)));
+        // Monday to Saturday in 2011
+        assert_eq!(
+            cal.business_days_between(
+                Calendar::from_ymd(2011, 1, 3),
+                Calendar::from_ymd(2011, 1, 9),
+                Bounds::Inclusive
+            ),
+            6
+        );
+        assert_eq!(
+            serde_json::to_value(&rules).unwrap()[1]["BoundedWeekDay"].get("first"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lazy_calendar() {
+        let cal = UsExchangeCalendar::with_default_range(false).into_lazy();
+        assert!(cal.populated_years().is_empty());
+        assert!(cal.is_holiday(Calendar::from_ymd(2022, 7, 4)));
+        assert!(!cal.is_business_day(Calendar::from_ymd(2022, 7, 4)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2022, 7, 5)));
+        assert_eq!(cal.populated_years(), vec![2022]);
+        assert_eq!(
+            cal.half_day_close(Calendar::from_ymd(2035, 11, 23)),
+            NaiveTime::from_hms_opt(13, 0, 0)
+        );
+        assert_eq!(
+            cal.next_biz_day(Calendar::from_ymd(2022, 12, 30)),
+            Calendar::from_ymd(2023, 1, 3)
+        );
+        assert_eq!(cal.populated_years(), vec![2022, 2023, 2035]);
+
+        // settings of the calendar are carried over
+        let closure = Calendar::from_ymd(2022, 3, 8);
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let three = NaiveTime::from_hms_opt(15, 0, 0).unwrap();
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal
+            .with_regular_hours(nine, three)
+            .with_inception(Some(Calendar::from_ymd(2000, 1, 3)))
+            .add_override(CalendarOverride::Closure(closure));
+        let cal = uscal.into_lazy();
+        assert!(cal.is_holiday(closure));
+        assert!(!cal.is_business_day(Calendar::from_ymd(1999, 12, 30)));
+        assert!(cal.is_business_day(Calendar::from_ymd(2000, 1, 3)));
+        assert_eq!(
+            cal.with_year(closure, |cal| cal.regular_hours()),
+            (nine, three)
+        );
+    }
+
+    #[test]
+    fn test_rule_bounds_honored() {
+        let (first, last) = (Some(2005), Some(2010));
+        let rules = vec![
+            Holiday::MovableYearlyDay {
+                month: 12,
+                day: 25,
+                first,
+                last,
+                half_check: Some(HalfCheck::Before),
+                half_check_years: BTreeMap::new(),
+                except: vec![],
+                only_leap_years: None,
+                observance: None,
+                name: None,
+            },
+            Holiday::MovableYearlyDay {
+                month: 1,
+                day: 1,
+                first,
+                last,
+                half_check: Some(HalfCheck::After),
+                half_check_years: BTreeMap::new(),
+                except: vec![],
+                only_leap_years: None,
+                observance: Some(Observance::MondayIfWeekend),
+                name: None,
+            },
+            Holiday::EasterOffset {
+                offset: 1,
+                first,
+                last,
+                except: vec![],
+                only_leap_years: None,
+                adjust: Some(RollConvention::Following),
+                half_check: None,
+                name: None,
+            },
+            Holiday::MonthWeekday {
+                month: 11,
+                weekday: Weekday::Thu,
+                nth: NthWeek::Fourth,
+                first,
+                last,
+                half_check: Some(HalfCheck::After),
+                except: vec![],
+                only_leap_years: None,
+                offset_days: 0,
+                name: None,
+            },
+            Holiday::MonthWeekday {
+                month: 5,
+                weekday: Weekday::Mon,
+                nth: NthWeek::Last,
+                first,
+                last,
+                half_check: None,
+                except: vec![],
+                only_leap_years: None,
+                offset_days: -3,
+                name: None,
+            },
+        ];
+        for rule in rules {
+            let rules = [
+                Holiday::WeekDay(Weekday::Sat),
+                Holiday::WeekDay(Weekday::Sun),
+                rule,
+            ];
+            for (start, end) in [(2000, 2015), (2007, 2008), (2011, 2015)] {
+                let cal = Calendar::calc_calendar(&rules, start, end);
+                let range = (
+                    Calendar::from_ymd(1999, 1, 1),
+                    Calendar::from_ymd(2016, 12, 31),
+                );
+                let holidays = cal.holidays_in_range(range.0, range.1).collect::<Vec<_>>();
+                let halfdays = cal.half_holidays_in_range(range.0, range.1);
+                for date in holidays.iter().copied().chain(halfdays) {
+                    assert!(
+                        (2005..=2010).contains(&date.year())
+                            && (start..=end).contains(&date.year()),
+                        "{:?} produced {} when populated for {} to {}",
+                        rules[2],
+                        date,
+                        start,
+                        end
+                    );
+                }
+                let years =
+                    std::cmp::max(0, std::cmp::min(end, 2010) - std::cmp::max(start, 2005) + 1);
+                assert_eq!(holidays.len() as i32, years, "{:?}", rules[2]);
+            }
+        }
+        let rules = [Holiday::BoundedWeekDay {
+            weekday: Weekday::Sat,
+            first,
+            last,
+        }];
+        let cal = Calendar::calc_calendar(&rules, 2000, 2015);
+        assert!(!cal.is_weekend(Calendar::from_ymd(2004, 12, 25)));
+        assert!(cal.is_weekend(Calendar::from_ymd(2005, 1, 1)));
+        assert!(cal.is_weekend(Calendar::from_ymd(2010, 12, 25)));
+        assert!(!cal.is_weekend(Calendar::from_ymd(2011, 1, 1)));
+    }
+
+    #[test]
+    fn test_business_day_rank() {
+        let mut cal = UsExchangeCalendar::with_default_range(false)
+            .populate_cal(Some(2021), Some(2022))
+            .get_cal();
+        let check = |cal: &Calendar| {
+            assert_eq!(cal.business_day_rank(Calendar::from_ymd(2021, 1, 1)), None);
+            assert_eq!(
+                cal.business_day_rank(Calendar::from_ymd(2021, 1, 4)),
+                Some(0)
+            );
+            assert_eq!(
+                cal.business_day_at_rank(0),
+                Some(Calendar::from_ymd(2021, 1, 4))
+            );
+            assert_eq!(cal.business_day_rank(Calendar::from_ymd(2023, 1, 3)), None);
+            let mut expected = 0;
+            for date in Calendar::from_ymd(2021, 1, 1)
+                .iter_days()
+                .take_while(|date| date.year() < 2023)
+                .filter(|date| cal.is_business_day(*date))
+            {
+                assert_eq!(cal.business_day_rank(date), Some(expected));
+                assert_eq!(cal.business_day_at_rank(expected), Some(date));
+                expected += 1;
+            }
+            assert_eq!(cal.business_day_at_rank(expected), None);
+        };
+        check(&cal);
+        cal.build_count_index();
+        check(&cal);
+    }
+
+    #[test]
+    fn test_half_check_both() {
+        let holiday = |day| Holiday::MovableYearlyDay {
+            month: 3,
+            day,
+            first: None,
+            last: None,
+            half_check: Some(HalfCheck::Both),
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: None,
+        };
+        // Mar 9th 2022 is a Wednesday, Mar 14th a Monday and Mar 18th a Friday
+        let cal = Calendar::calc_calendar(&[holiday(9), holiday(14), holiday(18)], 2022, 2022);
+        let halfdays = cal
+            .half_holidays_in_range(
+                Calendar::from_ymd(2022, 3, 1),
+                Calendar::from_ymd(2022, 3, 31),
+            )
+            .map(|date| date.day())
+            .collect::<Vec<_>>();
+        assert_eq!(halfdays, vec![8, 10, 15, 17]);
+    }
+
+    #[test]
+    fn test_us_holiday_names() {
+        let names = default_us_holiday_rules()
+            .iter()
+            .filter_map(|rule| rule.name().map(str::to_string))
+            .collect::<Vec<_>>();
+        assert_eq!(names, US_HOLIDAY_NAMES);
+        assert_eq!(US_HOLIDAY_NAMES.len(), 10);
+    }
+
+    #[test]
+    fn test_zero_length_session() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.populate_cal(Some(2022), Some(2022));
+        let mut cal = uscal.get_cal();
+        let halted = Calendar::from_ymd(2022, 3, 3);
+        cal.add_override_early_close(halted, REGULAR_OPEN);
+        assert!(cal.is_business_day(halted));
+        assert!(!cal.is_trading_day(halted));
+        assert_eq!(cal.holiday_status(halted), HolidayStatus::HalfDay);
+        assert_eq!(cal.half_day_close(halted), Some(REGULAR_OPEN));
+        // regular half-days and business days are trading days
+        assert!(cal.is_trading_day(Calendar::from_ymd(2022, 11, 25)));
+        assert!(cal.is_trading_day(Calendar::from_ymd(2022, 3, 4)));
+        assert!(!cal.is_trading_day(Calendar::from_ymd(2022, 7, 4)));
+    }
+
+    #[test]
+    fn test_calc_calendar_range() {
+        let rules = default_us_holiday_rules();
+        assert_eq!(
+            Calendar::calc_calendar_range(&rules, 2000..=2050),
+            Calendar::calc_calendar(&rules, 2000, 2050)
+        );
+    }
+
+    #[test]
+    fn test_day_timeline() {
+        let cal = make_cal();
+        let timeline = cal.day_timeline(
+            Calendar::from_ymd(2022, 11, 24),
+            Calendar::from_ymd(2022, 11, 28),
+        );
+        let kinds = timeline.iter().map(|(_, kind)| *kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                HolidayStatus::Holiday,
+                HolidayStatus::HalfDay,
+                HolidayStatus::Weekend,
+                HolidayStatus::Weekend,
+                HolidayStatus::BusinessDay,
+            ]
+        );
+        assert_eq!(timeline[4].0, Calendar::from_ymd(2022, 11, 28));
+    }
+
+    #[test]
+    fn test_inception() {
+        let inception = Calendar::from_ymd(1971, 2, 8);
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.with_inception(Some(inception));
+        uscal.populate_cal(Some(1970), Some(1972));
+        let cal = uscal.get_cal();
+        let before = Calendar::from_ymd(1971, 2, 5);
+        assert_eq!(cal.holiday_status(before), HolidayStatus::PreInception);
+        assert_eq!(
+            cal.holiday_status(Calendar::from_ymd(1950, 6, 1)),
+            HolidayStatus::PreInception
+        );
+        assert!(!cal.is_business_day(before));
+        assert_eq!(cal.holiday_status(inception), HolidayStatus::BusinessDay);
+        assert_eq!(cal.next_biz_day(before), inception);
+        let json = serde_json::to_string(&uscal).unwrap();
+        let restored: UsExchangeCalendar = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_cal().inception(), Some(inception));
+    }
+
+    #[test]
+    fn test_remove_half_day() {
+        let mut cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        let date = Calendar::from_ymd(2019, 12, 24);
+        assert!(cal.is_half_holiday(date));
+        assert!(cal.remove_half_day(date));
+        assert!(!cal.is_half_holiday(date));
+        assert_eq!(cal.half_day_close(date), None);
+        assert!(cal.is_business_day(date));
+        assert!(!cal.remove_half_day(date));
+        assert!(!cal.remove_half_day(Calendar::from_ymd(2019, 12, 25)));
+        assert!(cal.is_holiday(Calendar::from_ymd(2019, 12, 25)));
+    }
+
+    #[test]
+    fn test_signed_business_days_between() {
+        let cal = UsExchangeCalendar::with_default_range(true).get_cal();
+        // Thursday before and Tuesday after Good Friday 2020
+        let a = Calendar::from_ymd(2020, 4, 9);
+        let b = Calendar::from_ymd(2020, 4, 14);
+        for bounds in [
+            Bounds::Inclusive,
+            Bounds::Exclusive,
+            Bounds::IncludeStart,
+            Bounds::IncludeEnd,
+        ] {
+            let forward = cal.signed_business_days_between(a, b, bounds);
+            assert_eq!(cal.signed_business_days_between(b, a, bounds), -forward);
+            assert_eq!(cal.business_days_between(b, a, bounds), forward as usize);
+        }
+        assert_eq!(cal.signed_business_days_between(a, b, Bounds::Inclusive), 3);
+        assert_eq!(
+            cal.signed_business_days_between(b, a, Bounds::IncludeStart),
+            -2
+        );
+        assert_eq!(
+            cal.signed_business_days_between(b, a, Bounds::Exclusive),
+            -1
+        );
+        assert_eq!(cal.signed_business_days_between(a, a, Bounds::Exclusive), 0);
+        // Good Friday as one end point, IncludeStart counts the first argument in either order
+        let a = Calendar::from_ymd(2020, 4, 10);
+        assert_eq!(
+            cal.signed_business_days_between(a, b, Bounds::IncludeStart),
+            1
+        );
+        assert_eq!(
+            cal.signed_business_days_between(b, a, Bounds::IncludeStart),
+            -2
+        );
+        assert_eq!(
+            cal.signed_business_days_between(b, a, Bounds::IncludeEnd),
+            -1
+        );
+        for bounds in [Bounds::Inclusive, Bounds::Exclusive] {
+            let forward = cal.signed_business_days_between(a, b, bounds);
+            assert_eq!(cal.signed_business_days_between(b, a, bounds), -forward);
+        }
+    }
+
+    #[test]
+    fn test_overrides_survive_repopulating() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.populate_cal(Some(2024), Some(2025));
+        let closure = Calendar::from_ymd(2025, 1, 9);
+        let christmas_eve = Calendar::from_ymd(2024, 12, 24);
+        uscal
+            .add_override(CalendarOverride::Closure(closure))
+            .add_override(CalendarOverride::RemoveHalfDay(christmas_eve));
+        assert!(uscal.get_cal().is_holiday(closure));
+
+        // a rule added afterwards doesn't undo the overrides
+        uscal.add_holiday_rule(Holiday::SingularHalfDay(closure));
+        let rebuilt = uscal.clone_with_rules_reapplied();
+        let cal = rebuilt.get_cal();
+        assert!(cal.is_holiday(closure));
+        assert!(!cal.is_half_holiday(closure));
+        assert!(!cal.is_half_holiday(christmas_eve));
+        assert_eq!(cal.covered_range(), uscal.get_cal().covered_range());
+
+        uscal.populate_cal(Some(2020), Some(2025));
+        assert!(uscal.get_cal().is_holiday(closure));
+        let json = serde_json::to_string(&uscal).unwrap();
+        let restored: UsExchangeCalendar = serde_json::from_str(&json).unwrap();
+        assert!(!restored.get_cal().is_half_holiday(christmas_eve));
+    }
+
+    #[test]
+    fn test_has_rule() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        let closure = Holiday::SingularDay(Calendar::from_ymd(2025, 1, 9));
+        assert!(!uscal.has_rule(&closure));
+        uscal.add_holiday_rule(closure.clone());
+        assert!(uscal.has_rule(&closure));
+        assert!(uscal.has_rule(&Holiday::WeekDay(Weekday::Sat)));
+        assert!(!uscal.has_rule(&Holiday::SingularHalfDay(Calendar::from_ymd(2025, 1, 9))));
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let mut rules = default_us_holiday_rules();
+        rules.extend(nyse_special_closures());
+        // Easter Saturday rolled to Easter Monday by the weekend of the rule set
+        rules.push(Holiday::EasterOffset {
+            offset: -1,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: Some(RollConvention::Following),
+            half_check: None,
+            name: None,
+        });
+        let cal = Calendar::calc_calendar(&rules, 1999, 2031);
+        for (index, rule) in rules.iter().enumerate() {
+            if matches!(rule, Holiday::WeekDay(_) | Holiday::SingularHalfDay(_)) {
+                continue;
+            }
+            // the full-day closures match those of the whole rule set
+            let expected: Vec<NaiveDate> = Calendar::from_ymd(2000, 1, 1)
+                .iter_days()
+                .take_while(|date| date.year() <= 2030)
+                .filter(|date| cal.holiday_source(*date) == Some(index))
+                .collect();
+            let effective: Vec<NaiveDate> = (2000..=2030)
+                .flat_map(|year| rule.effective_dates(&rules, year))
+                .collect();
+            assert_eq!(effective, expected, "{:?}", rule);
+            let occurrences: Vec<NaiveDate> = rule.occurrences(&rules, 2000, 2030).collect();
+            assert!(expected.iter().all(|date| occurrences.contains(date)));
+            assert!(occurrences
+                .iter()
+                .all(|date| cal.is_holiday(*date) || cal.is_half_holiday(*date)));
+        }
+        let easter_monday = Calendar::from_ymd(2022, 4, 18);
+        let adjusted = rules.last().unwrap();
+        assert_eq!(
+            adjusted.occurrences(&rules, 2022, 2022).collect::<Vec<_>>(),
+            vec![easter_monday]
+        );
+        let christmas = &default_us_holiday_rules()[11];
+        assert_eq!(christmas.name(), Some("Christmas Day"));
+        let dates: Vec<NaiveDate> = christmas.occurrences(&rules, 2021, 2024).collect();
+        let expected = [
+            (2021, 12, 24),
+            (2022, 12, 26),
+            (2023, 12, 25),
+            (2024, 12, 24),
+            (2024, 12, 25),
+        ];
+        let expected: Vec<NaiveDate> = expected
+            .iter()
+            .map(|(year, month, day)| Calendar::from_ymd(*year, *month, *day))
+            .collect();
+        assert_eq!(dates, expected);
+
+        // early close on New Year's Eve for the holiday of the following year
+        let new_year = Holiday::MovableYearlyDay {
+            month: 1,
+            day: 1,
+            first: None,
+            last: None,
+            half_check: Some(HalfCheck::Before),
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: None,
+            name: None,
+        };
+        let dates: Vec<NaiveDate> = new_year.occurrences(&[], 2024, 2025).collect();
+        assert_eq!(
+            dates,
+            vec![
+                Calendar::from_ymd(2024, 1, 1),
+                Calendar::from_ymd(2024, 12, 31),
+                Calendar::from_ymd(2025, 1, 1),
+                Calendar::from_ymd(2025, 12, 31),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regular_hours() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.populate_cal(Some(2022), Some(2022));
+        let at = |day, hour, min| {
+            Calendar::from_ymd(2022, 3, day)
+                .and_hms_opt(hour, min, 0)
+                .unwrap()
+        };
+        assert!(uscal.is_market_open(at(7, 9, 30)));
+        assert!(uscal.is_market_open(at(7, 15, 59)));
+        assert!(!uscal.is_market_open(at(7, 16, 0)));
+        assert!(!uscal.is_market_open(at(6, 12, 0)));
+
+        let ten = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+        let three = NaiveTime::from_hms_opt(15, 0, 0).unwrap();
+        uscal.with_regular_hours(ten, three);
+        assert!(!uscal.is_market_open(at(7, 9, 30)));
+        assert!(uscal.is_market_open(at(7, 10, 0)));
+        assert!(!uscal.is_market_open(at(7, 15, 30)));
+        assert_eq!(
+            uscal.session(Calendar::from_ymd(2022, 3, 7)),
+            Some(Session::new(ten, three))
+        );
+        // the calendar itself uses the hours, also after repopulating
+        uscal.populate_cal(Some(2022), Some(2022));
+        let monday = Calendar::from_ymd(2022, 3, 7);
+        uscal.add_override(CalendarOverride::EarlyClose(monday, ten));
+        let cal = uscal.get_cal();
+        assert_eq!(cal.regular_hours(), (ten, three));
+        assert!(!cal.is_trading_day(monday));
+        assert_eq!(
+            cal.trading_sessions(monday, Calendar::from_ymd(2022, 3, 8)),
+            vec![(monday, ten), (Calendar::from_ymd(2022, 3, 8), three)]
+        );
 
-    fn make_cal() -> Calendar {
-        let usec = UsExchangeCalendar::with_default_range(true);
-        usec.get_cal()
+        let json = serde_json::to_string(&uscal).unwrap();
+        let restored: UsExchangeCalendar = serde_json::from_str(&json).unwrap();
+        assert!(!restored.is_market_open(at(7, 15, 30)));
+        let json = serde_json::to_string(&UsExchangeCalendar::with_default_range(false)).unwrap();
+        assert!(!json.contains("regular_hours"));
     }
 
     #[test]
-    fn fixed_dates_calendar() {
-        let holidays = vec![
-            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 20)),
-            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 24)),
-            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 25)),
-            Holiday::WeekDay(Weekday::Sat),
-            Holiday::WeekDay(Weekday::Sun),
-        ];
-        let cal = Calendar::calc_calendar(&holidays, 2019, 2019);
+    fn test_range_breakdown() {
+        let cal = make_cal();
+        // September 2022 with Labor Day on Monday the 5th
+        let start = Calendar::from_ymd(2022, 9, 1);
+        let end = Calendar::from_ymd(2022, 9, 30);
+        let breakdown = cal.range_breakdown(start, end);
+        assert_eq!(
+            breakdown,
+            RangeBreakdown {
+                business_days: 21,
+                holidays: 1,
+                weekend_days: 8,
+                half_days: 0,
+            }
+        );
+        assert_eq!(breakdown.total(), 30);
+        assert_eq!(
+            breakdown.business_days + breakdown.half_days,
+            cal.business_days_between(start, end, Bounds::Inclusive)
+        );
 
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 20)));
-        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 21)));
-        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 22)));
-        // weekend
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 23)));
-        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2019, 11, 23)));
-        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2019, 11, 23)));
-        // weekend and holiday
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 24)));
-        assert_eq!(true, cal.is_weekend(Calendar::from_ymd(2019, 11, 24)));
-        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2019, 11, 24)));
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2019, 11, 25)));
-        assert_eq!(true, cal.is_business_day(Calendar::from_ymd(2019, 11, 26)));
+        // Christmas on a Sunday, observed Monday, no early close
+        let breakdown = cal.range_breakdown(
+            Calendar::from_ymd(2022, 12, 19),
+            Calendar::from_ymd(2022, 12, 26),
+        );
+        assert_eq!((breakdown.holidays, breakdown.weekend_days), (1, 2));
+        // Thanksgiving with the early close on Friday
+        let breakdown = cal.range_breakdown(
+            Calendar::from_ymd(2022, 11, 21),
+            Calendar::from_ymd(2022, 11, 27),
+        );
+        assert_eq!(
+            breakdown,
+            RangeBreakdown {
+                business_days: 3,
+                holidays: 1,
+                weekend_days: 2,
+                half_days: 1,
+            }
+        );
+        assert_eq!(cal.range_breakdown(end, start).total(), 0);
     }
 
     #[test]
-    fn test_movable_yearly_day() {
-        let holidays = vec![Holiday::MovableYearlyDay {
-            month: 1,
-            day: 1,
+    fn test_last_full_week() {
+        // May 2024 ends on a Friday, the last full week is May 20th to 26th
+        let last = nth_weekday_of_month(2024, 5, Weekday::Mon, &NthWeek::Last);
+        let full = nth_weekday_of_month(2024, 5, Weekday::Mon, &NthWeek::LastFullWeek);
+        assert_eq!(last, Calendar::from_ymd(2024, 5, 27));
+        assert_eq!(full, Calendar::from_ymd(2024, 5, 20));
+        assert_eq!(
+            nth_weekday_of_month(2024, 5, Weekday::Sun, &NthWeek::LastFullWeek),
+            Calendar::from_ymd(2024, 5, 26)
+        );
+        // March 2024 ends on a Sunday, both agree
+        assert_eq!(
+            nth_weekday_of_month(2024, 3, Weekday::Mon, &NthWeek::LastFullWeek),
+            nth_weekday_of_month(2024, 3, Weekday::Mon, &NthWeek::Last)
+        );
+
+        let rule = Holiday::MonthWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+            nth: NthWeek::LastFullWeek,
             first: None,
             last: None,
             half_check: None,
-        }];
-        let cal = Calendar::calc_calendar(&holidays, 2021, 2022);
-        assert_eq!(false, cal.is_holiday(Calendar::from_ymd(2021, 12, 31)));
+            except: vec![],
+            only_leap_years: None,
+            offset_days: 0,
+            name: None,
+        };
+        let cal = Calendar::calc_calendar(&[rule], 2024, 2024);
+        assert!(cal.is_holiday(full));
+        assert!(!cal.is_holiday(last));
+        let json = serde_json::to_string(&NthWeek::LastFullWeek).unwrap();
+        assert_eq!(json, r#""LastFullWeek""#);
     }
 
     #[test]
-    /// Good Friday example
-    fn test_easter_offset() {
-        let holidays = vec![Holiday::EasterOffset {
-            offset: -2,
-            first: None,
-            last: None,
-        }];
-        let cal = Calendar::calc_calendar(&holidays, 2021, 2022);
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2021, 4, 2)));
-        assert_eq!(false, cal.is_business_day(Calendar::from_ymd(2022, 4, 15)));
+    fn test_schedule_report() {
+        let expected = "\
+2025-01-01 New Year's Day (closed)
+2025-01-20 Martin Luther King Jr. Day (closed)
+2025-02-17 Washington's Birthday (closed)
+2025-04-18 Good Friday (closed)
+2025-05-26 Memorial Day (closed)
+2025-06-19 Juneteenth National Independence Day (closed)
+2025-07-03 Day before Independence Day (early close 1:00 PM)
+2025-07-04 Independence Day (closed)
+2025-09-01 Labor Day (closed)
+2025-11-27 Thanksgiving Day (closed)
+2025-11-28 Day after Thanksgiving Day (early close 1:00 PM)
+2025-12-24 Day before Christmas Day (early close 1:00 PM)
+2025-12-25 Christmas Day (closed)
+";
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        assert_eq!(uscal.schedule_report(2025), expected);
+        uscal.populate_cal(Some(2024), Some(2026));
+        assert_eq!(uscal.schedule_report(2025), expected);
+        let sifma = UsExchangeCalendar::sifma(false).schedule_report(2025);
+        assert!(sifma.contains("2025-11-28 Day after Thanksgiving Day (early close 2:00 PM)\n"));
     }
 
     #[test]
-    fn test_month_weekday() {
-        let holidays = vec![
-            // MLK
-            Holiday::MonthWeekday {
-                month: 1,
-                weekday: Weekday::Mon,
-                nth: NthWeek::Third,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-            // President's Day
-            Holiday::MonthWeekday {
-                month: 2,
-                weekday: Weekday::Mon,
-                nth: NthWeek::Third,
-                first: None,
-                last: None,
-                half_check: None,
-            },
-        ];
-        let cal = Calendar::calc_calendar(&holidays, 2022, 2022);
-        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 1, 17)));
-        assert_eq!(true, cal.is_holiday(Calendar::from_ymd(2022, 2, 21)));
+    fn test_no_implicit_weekend() {
+        let saturday = Calendar::from_ymd(2022, 3, 5);
+        let rules = vec![Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3))];
+        let cal = UsExchangeCalendar::from_rules(rules.clone(), false)
+            .build_calendar(Some(2022), Some(2022));
+        assert!(cal.weekdays().is_empty());
+        assert!(!cal.is_weekend(saturday));
+        assert!(cal.is_business_day(saturday));
+
+        let mut rules = rules;
+        rules.push(Holiday::WeekDay(Weekday::Sat));
+        rules.push(Holiday::WeekDay(Weekday::Sun));
+        let cal =
+            UsExchangeCalendar::from_rules(rules, false).build_calendar(Some(2022), Some(2022));
+        assert!(cal.is_weekend(saturday));
+        assert!(!cal.is_business_day(saturday));
     }
 
     #[test]
-    /// Testing serialization and deserialization of holidays definitions
-    fn serialize_cal_definition() {
-        let holidays = vec![
-            Holiday::MonthWeekday {
-                month: 11,
-                weekday: Weekday::Mon,
-                nth: NthWeek::First,
-                first: None,
-                last: None,
-                half_check: None,
-            },
+    fn test_ensure_year() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        uscal.ensure_year(2023);
+        assert_eq!(
+            uscal.get_cal().covered_range(),
+            Some((
+                Calendar::from_ymd(2023, 1, 1),
+                Calendar::from_ymd(2023, 12, 31)
+            ))
+        );
+        let closure = Calendar::from_ymd(2024, 3, 4);
+        uscal.add_override(CalendarOverride::Closure(closure));
+        uscal.ensure_year(2025).ensure_year(2021).ensure_year(2024);
+        let cal = uscal.get_cal();
+        assert_eq!(
+            cal.covered_range(),
+            Some((
+                Calendar::from_ymd(2021, 1, 1),
+                Calendar::from_ymd(2025, 12, 31)
+            ))
+        );
+        assert!(cal.is_holiday(closure));
+        assert_eq!(
+            cal.holiday_name(Calendar::from_ymd(2025, 7, 4)),
+            Some("Independence Day")
+        );
+
+        let mut expected = UsExchangeCalendar::with_default_range(false);
+        expected.populate_cal(Some(2021), Some(2025));
+        let mut expected = expected.get_cal();
+        expected.add_override_closure(closure);
+        assert_eq!(cal, expected);
+
+        // an early close before New Year's Day 2025 on a holiday covered already
+        let new_years_eve = Calendar::from_ymd(2024, 12, 31);
+        let rules = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
             Holiday::MovableYearlyDay {
-                month: 11,
+                month: 1,
                 day: 1,
-                first: Some(2016),
-                last: None,
-                half_check: None,
-            },
-            Holiday::SingularDay(Calendar::from_ymd(2019, 11, 25)),
-            Holiday::WeekDay(Weekday::Sat),
-            Holiday::EasterOffset {
-                offset: -2,
                 first: None,
                 last: None,
+                half_check: Some(HalfCheck::Before),
+                half_check_years: BTreeMap::new(),
+                except: vec![],
+                only_leap_years: None,
+                observance: None,
+                name: None,
             },
+            Holiday::SingularDay(new_years_eve),
         ];
-        let json = serde_json::to_string_pretty(&holidays).unwrap();
-        assert_eq!(
-            json,
-            r#"[
-  {
-    "MonthWeekday": {
-      "month": 11,
-      "weekday": "Mon",
-      "nth": "First",
-      "first": null,
-      "last": null,
-      "half_check": null
-    }
-  },
-  {
-    "MovableYearlyDay": {
-      "month": 11,
-      "day": 1,
-      "first": 2016,
-      "last": null,
-      "half_check": null
-    }
-  },
-  {
-    "SingularDay": "2019-11-25"
-  },
-  {
-    "WeekDay": "Sat"
-  },
-  {
-    "EasterOffset": {
-      "offset": -2,
-      "first": null,
-      "last": null
-    }
-  }
-]"#
-        );
-        let holidays2: Vec<Holiday> = serde_json::from_str(&json).unwrap();
-        assert_eq!(holidays.len(), holidays2.len());
-        for i in 0..holidays.len() {
-            assert_eq!(holidays[i], holidays2[i]);
-        }
-    }
+        let mut uscal = UsExchangeCalendar::from_rules(rules.clone(), false);
+        uscal.ensure_year(2024).ensure_year(2025);
+        assert!(!uscal.get_cal().is_half_holiday(new_years_eve));
+        let mut expected = UsExchangeCalendar::from_rules(rules, false);
+        expected.populate_cal(Some(2024), Some(2025));
+        assert_eq!(uscal.get_cal(), expected.get_cal());
 
-    #[test]
-    fn test_usexchange_calendar_empty() {
-        let sc = UsExchangeCalendar::with_default_range(false);
-        let c = sc.get_cal();
-        assert!(c.holidays.len() == 0);
-        assert!(c.halfdays.len() == 0);
-        assert!(c.weekdays.len() == 0);
-    }
-
-    #[test]
-    fn test_usexchange_calendar_populated() {
-        let sc = UsExchangeCalendar::with_default_range(true);
-        let c = sc.get_cal();
-        assert!(c.holidays.len() > 0);
-        assert!(c.halfdays.len() > 0);
-        assert!(c.weekdays.len() > 0);
-        assert!(c.is_holiday(Calendar::from_ymd(2021, 1, 1)));
-        assert_eq!(false, c.is_holiday(Calendar::from_ymd(2021, 12, 31)))
+        // Easter can't be computed before 1583
+        let covered = uscal.get_cal().covered_range();
+        assert!(matches!(
+            uscal
+                .add_holiday_rule(Holiday::EasterOffset {
+                    offset: -2,
+                    first: None,
+                    last: None,
+                    except: vec![],
+                    only_leap_years: None,
+                    adjust: None,
+                    half_check: None,
+                    name: None,
+                })
+                .try_ensure_year(1500),
+            Err(Error::UnsupportedYear(1500))
+        ));
+        assert_eq!(uscal.get_cal().covered_range(), covered);
     }
 
     #[test]
-    fn test_usexchange_calendar_with_new_rule() {
-        // imaginary holiday, let's call it March Madness Day
-        let mut sc = UsExchangeCalendar::with_default_range(false);
-        let holiday = Holiday::MonthWeekday {
-            month: 3,
-            weekday: Weekday::Wed,
-            nth: NthWeek::Third,
+    fn test_effective_dates() {
+        let rules = default_us_holiday_rules();
+        let thanksgiving = rules
+            .iter()
+            .find(|rule| rule.name() == Some("Thanksgiving Day"))
+            .unwrap();
+        assert_eq!(
+            thanksgiving.effective_dates(&rules, 2025),
+            vec![Calendar::from_ymd(2025, 11, 27)]
+        );
+        // the early close on Friday is part of the occurrences only
+        assert_eq!(thanksgiving.occurrences(&rules, 2025, 2025).count(), 2);
+        // Juneteenth is observed since 2022 only
+        let juneteenth = &rules[7];
+        assert_eq!(
+            juneteenth.name(),
+            Some("Juneteenth National Independence Day")
+        );
+        assert!(juneteenth.effective_dates(&rules, 2021).is_empty());
+        assert_eq!(
+            Holiday::SingularHalfDay(Calendar::from_ymd(2025, 12, 24))
+                .effective_dates(&rules, 2025),
+            vec![Calendar::from_ymd(2025, 12, 24)]
+        );
+        assert_eq!(
+            Holiday::WeekDay(Weekday::Sat)
+                .effective_dates(&rules, 2025)
+                .len(),
+            52
+        );
+        // a New Year's Eve holiday observed in the following year
+        let new_years_eve = Holiday::MovableYearlyDay {
+            month: 12,
+            day: 31,
             first: None,
             last: None,
             half_check: None,
+            half_check_years: BTreeMap::new(),
+            except: vec![],
+            only_leap_years: None,
+            observance: Some(Observance::MondayIfWeekend),
+            name: None,
         };
-        sc.add_holiday_rule(holiday).populate_cal(None, None);
-        let c = sc.get_cal();
-        assert_eq!(true, c.is_holiday(Calendar::from_ymd(2022, 3, 16)));
+        assert_eq!(
+            new_years_eve.effective_dates(&rules, 2023),
+            vec![Calendar::from_ymd(2023, 1, 2)]
+        );
+        // no early closes on weekend days of the rule set
+        let saturdays = Holiday::RecurringHalfDay {
+            weekday: Weekday::Sat,
+            months: vec![7],
+            close_time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            first: None,
+            last: None,
+            name: None,
+        };
+        assert!(saturdays.effective_dates(&rules, 2025).is_empty());
+        assert_eq!(saturdays.effective_dates(&[], 2025).len(), 4);
     }
 
     #[test]
-    fn test_is_trading_date() {
+    fn test_juneteenth_observance() {
+        let name = "Juneteenth National Independence Day";
         let cal = make_cal();
-        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 4, 18)), false);
-        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 4, 19)), true);
-        assert_eq!(cal.is_business_day(Calendar::from_ymd(2021, 1, 1)), false);
+        let observed: Vec<NaiveDate> = cal
+            .holidays_in_range(
+                Calendar::from_ymd(2000, 1, 1),
+                Calendar::from_ymd(2028, 12, 31),
+            )
+            .filter(|date| cal.holiday_name(*date) == Some(name))
+            .collect();
+        let expected: Vec<NaiveDate> = [
+            (2022, 6, 20), // Sunday, observed Monday
+            (2023, 6, 19),
+            (2024, 6, 19),
+            (2025, 6, 19),
+            (2026, 6, 19),
+            (2027, 6, 18), // Saturday, observed Friday
+            (2028, 6, 19),
+        ]
+        .iter()
+        .map(|(year, month, day)| Calendar::from_ymd(*year, *month, *day))
+        .collect();
+        assert_eq!(observed, expected);
+        // June 19th 2021 was a Saturday, Friday the 18th stays a business day
+        assert!(cal.is_business_day(Calendar::from_ymd(2021, 6, 18)));
+        // same if the range starts in the first year of the holiday
+        let cal = Calendar::calc_calendar(&default_us_holiday_rules(), 2022, 2022);
+        assert_eq!(
+            cal.holiday_name(Calendar::from_ymd(2022, 6, 20)),
+            Some(name)
+        );
+        assert!(cal.is_business_day(Calendar::from_ymd(2022, 6, 17)));
     }
 
     #[test]
-    fn test_is_partial_trading_date() {
+    fn test_trading_days_until() {
         let cal = make_cal();
-        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2027, 12, 23)), false);
-        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2026, 7, 2)), false);
-        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2021, 11, 26)), true);
-        assert_eq!(cal.is_half_holiday(Calendar::from_ymd(2022, 5, 12)), false);
+        // Monday before the April 2025 monthly expiry on Thursday the 17th (Good Friday follows)
+        let today = Calendar::from_ymd(2025, 4, 14);
+        let expiry = Calendar::from_ymd(2025, 4, 17);
+        assert_eq!(cal.trading_days_until(today, expiry), Some(3));
+        assert_eq!(
+            cal.trading_days_until(today, Calendar::from_ymd(2025, 4, 21)),
+            Some(4)
+        );
+        assert_eq!(cal.trading_days_until(expiry, expiry), Some(0));
+        assert_eq!(cal.trading_days_until(expiry, today), None);
+        let (_, last) = cal.covered_range().unwrap();
+        assert_eq!(
+            cal.trading_days_until(today, last.succ_opt().unwrap()),
+            None
+        );
     }
 
+    #[cfg(feature = "bincode")]
     #[test]
-    fn test_prev_biz_day() {
-        let cal = make_cal();
+    fn test_bytes_round_trip() {
+        let mut cal = UsExchangeCalendar::sifma(true).get_cal();
+        cal.set_inception(Some(Calendar::from_ymd(2001, 1, 2)));
+        cal.set_regular_hours(
+            NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        );
+        let bytes = cal.to_bytes().unwrap();
+        let restored = Calendar::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, cal);
+        for date in Calendar::from_ymd(2000, 1, 1).iter_days().take(365 * 30) {
+            assert_eq!(restored.is_business_day(date), cal.is_business_day(date));
+            assert_eq!(restored.half_day_close(date), cal.half_day_close(date));
+        }
+        assert!(bytes.len() < serde_json::to_string(&cal).unwrap().len() / 2);
+        assert!(matches!(
+            Calendar::from_bytes(&bytes[..bytes.len() / 2]),
+            Err(Error::Binary(_))
+        ));
+    }
+
+    #[test]
+    fn test_easter_offset_half_check() {
+        let bonds = UsExchangeCalendar::sifma(true).get_cal();
+        let stocks = make_cal();
+        let good_friday = Calendar::from_ymd(2025, 4, 18);
+        let thursday = Calendar::from_ymd(2025, 4, 17);
+        for cal in [&bonds, &stocks] {
+            assert!(cal.is_holiday(good_friday));
+            assert_eq!(cal.holiday_name(good_friday), Some("Good Friday"));
+        }
         assert_eq!(
-            cal.prev_biz_day(Calendar::from_ymd(2021, 1, 18)),
-            Calendar::from_ymd(2021, 1, 15)
+            bonds.half_day_close(thursday),
+            NaiveTime::from_hms_opt(14, 0, 0)
         );
+        assert_eq!(stocks.half_day_close(thursday), None);
+
+        // Easter Monday with an early close on the next day
+        let rule = Holiday::EasterOffset {
+            offset: 1,
+            first: None,
+            last: None,
+            except: vec![],
+            only_leap_years: None,
+            adjust: None,
+            half_check: Some(HalfCheck::After),
+            name: Some("Easter Monday".to_string()),
+        };
+        let cal = Calendar::calc_calendar(std::slice::from_ref(&rule), 2025, 2025);
         assert_eq!(
-            cal.prev_biz_day(Calendar::from_ymd(2021, 4, 19)),
-            Calendar::from_ymd(2021, 4, 16)
+            cal.holiday_name(Calendar::from_ymd(2025, 4, 21)),
+            Some("Easter Monday")
         );
+        assert!(cal.is_half_holiday(Calendar::from_ymd(2025, 4, 22)));
+        let json = serde_json::to_string(&rule).unwrap();
+        assert!(json.contains(r#""half_check":"After""#));
+        let rule: Holiday = serde_json::from_str(
+            r#"{"EasterOffset": {"offset": -2, "first": null, "last": null}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            rule,
+            Holiday::EasterOffset {
+                half_check: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_trading_span() {
+        let mut uscal = UsExchangeCalendar::with_default_range(false);
+        assert_eq!(uscal.get_cal().trading_span(), None);
+        uscal.populate_cal(Some(2022), Some(2022));
+        let cal = uscal.get_cal();
+        // New Year's Day 2022 was a Saturday and is not observed, Dec 31st 2022 a Saturday
+        let (first, last) = cal.trading_span().unwrap();
+        assert_eq!(first, Calendar::from_ymd(2022, 1, 3));
+        assert_eq!(last, Calendar::from_ymd(2022, 12, 30));
+        assert!(cal.is_business_day(first) && cal.is_business_day(last));
+
+        // Saturdays off only, both ends of 2022 are Saturdays
+        let weekend = Calendar::calc_calendar(&[Holiday::WeekDay(Weekday::Sat)], 2022, 2022);
         assert_eq!(
-            cal.prev_biz_day(Calendar::from_ymd(2021, 8, 9)),
-            Calendar::from_ymd(2021, 8, 6)
+            weekend.trading_span(),
+            Some((
+                Calendar::from_ymd(2022, 1, 2),
+                Calendar::from_ymd(2022, 12, 30)
+            ))
         );
     }
 
     #[test]
-    fn test_next_biz_day() {
+    fn test_next_early_close() {
         let cal = make_cal();
+        let thanksgiving = Calendar::from_ymd(2025, 11, 27);
+        let day_after = Calendar::from_ymd(2025, 11, 28);
+        let one_pm = NaiveTime::from_hms_opt(13, 0, 0).unwrap();
         assert_eq!(
-            cal.next_biz_day(Calendar::from_ymd(2021, 4, 16)),
-            Calendar::from_ymd(2021, 4, 19)
+            cal.next_early_close(Calendar::from_ymd(2025, 7, 3)),
+            Some((day_after, one_pm))
         );
         assert_eq!(
-            cal.next_biz_day(Calendar::from_ymd(2021, 4, 19)),
-            Calendar::from_ymd(2021, 4, 20)
+            cal.next_early_close(thanksgiving),
+            Some((day_after, one_pm))
         );
         assert_eq!(
-            cal.next_biz_day(Calendar::from_ymd(2021, 4, 2)),
-            Calendar::from_ymd(2021, 4, 5)
+            cal.next_early_close(day_after),
+            Some((Calendar::from_ymd(2025, 12, 24), one_pm))
+        );
+
+        let mut cal = cal;
+        let halted = Calendar::from_ymd(2025, 10, 1);
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        cal.add_override_early_close(halted, noon);
+        assert_eq!(
+            cal.next_early_close(Calendar::from_ymd(2025, 9, 1)),
+            Some((halted, noon))
+        );
+        let (_, last) = cal.covered_range().unwrap();
+        assert_eq!(cal.next_early_close(last), None);
+        assert_eq!(
+            Calendar::from_weekmask("1111100", &[])
+                .unwrap()
+                .next_early_close(day_after),
+            None
         );
     }
 }
@@ -1 +1,3 @@
-pub mod calendar;
\ No newline at end of file
+pub mod calendar;
+pub mod market;
+pub mod prelude;
@@ -0,0 +1,318 @@
+//! Registry of named calendars, e.g. one per exchange, for questions spanning several markets.
+
+use crate::calendar::{Calendar, Error, Holiday, HolidayStatus, UsExchangeCalendar};
+use chrono::{NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A set of calendars registered by name
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Market {
+    calendars: BTreeMap<String, Calendar>,
+}
+
+impl Market {
+    /// Create an empty market registry
+    pub fn new() -> Market {
+        Market {
+            calendars: BTreeMap::new(),
+        }
+    }
+
+    /// Load one calendar per `*.json` file in `path`, each file holding a JSON list of holiday
+    /// rules. The file name without extension is used as the calendar name, calendars are
+    /// populated with the default range of [`UsExchangeCalendar::populate_cal`].
+    pub fn from_dir(path: &Path) -> Result<Market, Error> {
+        Market::from_dir_with_range(path, None, None)
+    }
+
+    /// Same as [`Market::from_dir`] but populates the calendars for the years `start` to `end`
+    /// (inclusively, defaults as in [`UsExchangeCalendar::populate_cal`] if None, None are given)
+    pub fn from_dir_with_range(
+        path: &Path,
+        start: Option<i32>,
+        end: Option<i32>,
+    ) -> Result<Market, Error> {
+        let io_error = |path: &Path, err: std::io::Error| Error::Io {
+            path: path.display().to_string(),
+            message: err.to_string(),
+        };
+        let mut market = Market::new();
+        for entry in fs::read_dir(path).map_err(|err| io_error(path, err))? {
+            let file = entry.map_err(|err| io_error(path, err))?.path();
+            if file.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let name = match file.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let json = fs::read_to_string(&file).map_err(|err| io_error(&file, err))?;
+            let rules: Vec<Holiday> =
+                serde_json::from_str(&json).map_err(|err| Error::InvalidRules {
+                    path: file.display().to_string(),
+                    message: err.to_string(),
+                })?;
+            let mut sc = UsExchangeCalendar::from_rules(rules, false);
+            sc.try_populate_cal(start, end)?;
+            market.add_calendar(&name, sc.get_cal());
+        }
+        Ok(market)
+    }
+
+    /// Serialize all calendars into a single JSON document mapping calendar names to calendars
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|err| Error::Json(err.to_string()))
+    }
+
+    /// Restore a market from a JSON document created by [`Market::to_json`]
+    pub fn from_json(json: &str) -> Result<Market, Error> {
+        serde_json::from_str(json).map_err(|err| Error::Json(err.to_string()))
+    }
+
+    /// register `calendar` under `name`, replacing any calendar previously registered with that name
+    pub fn add_calendar(&mut self, name: &str, calendar: Calendar) -> &mut Self {
+        self.calendars.insert(name.to_string(), calendar);
+        self
+    }
+
+    /// Register a calendar computed from `rules` with its own `weekend` days, e.g. Friday and
+    /// Saturday for a Gulf market, populated for the years `start` to `end` (defaults as in
+    /// [`UsExchangeCalendar::populate_cal`])
+    pub fn add_calendar_with_weekend(
+        &mut self,
+        name: &str,
+        weekend: &[Weekday],
+        rules: Vec<Holiday>,
+        start: Option<i32>,
+        end: Option<i32>,
+    ) -> Result<&mut Self, Error> {
+        let mut holiday_rules: Vec<Holiday> =
+            weekend.iter().map(|day| Holiday::WeekDay(*day)).collect();
+        holiday_rules.extend(rules);
+        let mut sc = UsExchangeCalendar::from_rules(holiday_rules, false);
+        sc.try_populate_cal(start, end)?;
+        Ok(self.add_calendar(name, sc.get_cal()))
+    }
+
+    /// Returns the calendar registered under `name`
+    pub fn get_calendar(&self, name: &str) -> Option<&Calendar> {
+        self.calendars.get(name)
+    }
+
+    /// Status of `date` in the calendar registered under `name`, `None` if the name is unknown
+    pub fn status(&self, name: &str, date: NaiveDate) -> Option<HolidayStatus> {
+        Some(self.get_calendar(name)?.holiday_status(date))
+    }
+
+    /// Status of `date` in every calendar by name, calendars disagreeing on a date (e.g. a bond
+    /// market holiday on an equity trading day) keep their own status
+    pub fn statuses(&self, date: NaiveDate) -> BTreeMap<String, HolidayStatus> {
+        self.calendars
+            .iter()
+            .map(|(name, cal)| (name.clone(), cal.holiday_status(date)))
+            .collect()
+    }
+
+    /// Returns true if `date` is a business day in every named calendar, `None` if any
+    /// of the names is unknown
+    pub fn all_open(&self, names: &[&str], date: NaiveDate) -> Option<bool> {
+        let mut open = true;
+        for name in names {
+            open &= self.get_calendar(name)?.is_business_day(date);
+        }
+        Some(open)
+    }
+
+    /// Returns true if `date` is a business day in at least one of the named calendars,
+    /// `None` if any of the names is unknown
+    pub fn any_open(&self, names: &[&str], date: NaiveDate) -> Option<bool> {
+        let mut open = false;
+        for name in names {
+            open |= self.get_calendar(name)?.is_business_day(date);
+        }
+        Some(open)
+    }
+
+    /// Returns the names of all calendars with a business day on `date` in ascending order
+    pub fn open_calendars(&self, date: NaiveDate) -> Vec<String> {
+        self.calendars
+            .iter()
+            .filter(|(_, cal)| cal.is_business_day(date))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::Holiday;
+
+    fn make_market() -> Market {
+        let weekend = vec![
+            Holiday::WeekDay(Weekday::Sat),
+            Holiday::WeekDay(Weekday::Sun),
+        ];
+        let mut rules = weekend.clone();
+        rules.push(Holiday::SingularDay(Calendar::from_ymd(2022, 3, 3)));
+        let mut market = Market::new();
+        market
+            .add_calendar("open", Calendar::calc_calendar(&weekend, 2022, 2022))
+            .add_calendar("closed", Calendar::calc_calendar(&rules, 2022, 2022));
+        market
+    }
+
+    #[test]
+    fn test_all_open_any_open() {
+        let market = make_market();
+        let date = Calendar::from_ymd(2022, 3, 3);
+        assert_eq!(market.all_open(&["open", "closed"], date), Some(false));
+        assert_eq!(market.any_open(&["open", "closed"], date), Some(true));
+        assert_eq!(market.all_open(&["open"], date), Some(true));
+        assert_eq!(market.any_open(&["closed"], date), Some(false));
+
+        let date = Calendar::from_ymd(2022, 3, 4);
+        assert_eq!(market.all_open(&["open", "closed"], date), Some(true));
+
+        assert_eq!(market.all_open(&["open", "unknown"], date), None);
+        assert_eq!(market.any_open(&["unknown", "open"], date), None);
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/markets");
+        let market = Market::from_dir_with_range(&dir, Some(2022), Some(2023)).unwrap();
+        let nyse = market.get_calendar("nyse").unwrap();
+        let tase = market.get_calendar("tase").unwrap();
+        assert!(market.get_calendar("README").is_none());
+        // Good Friday and a regular Friday
+        assert!(!nyse.is_business_day(Calendar::from_ymd(2022, 4, 15)));
+        let date = Calendar::from_ymd(2022, 4, 8);
+        assert!(nyse.is_business_day(date));
+        assert!(!tase.is_business_day(date));
+        // Sunday
+        let date = Calendar::from_ymd(2022, 4, 17);
+        assert!(!nyse.is_business_day(date));
+        assert!(tase.is_business_day(date));
+        assert_eq!(
+            nyse.covered_range(),
+            Some((
+                Calendar::from_ymd(2022, 1, 1),
+                Calendar::from_ymd(2023, 12, 31)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_from_dir_errors() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/invalid_markets");
+        match Market::from_dir(&dir) {
+            Err(Error::InvalidRules { path, .. }) => assert!(path.ends_with("broken.json")),
+            other => panic!("unexpected result {:?}", other),
+        }
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/missing");
+        assert!(matches!(Market::from_dir(&dir), Err(Error::Io { .. })));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut market = make_market();
+        market.add_calendar(
+            "nyse",
+            UsExchangeCalendar::with_default_range(true).get_cal(),
+        );
+        let json = market.to_json().unwrap();
+        let reloaded = Market::from_json(&json).unwrap();
+        for name in ["open", "closed", "nyse"] {
+            let calendar = market.get_calendar(name).unwrap();
+            let reloaded = reloaded.get_calendar(name).unwrap();
+            assert_eq!(calendar, reloaded);
+            for date in Calendar::from_ymd(2022, 1, 1).iter_days().take(365) {
+                assert_eq!(
+                    calendar.is_business_day(date),
+                    reloaded.is_business_day(date)
+                );
+            }
+        }
+        assert!(json.contains(r#""weekdays":["Sat","Sun"]"#));
+        assert!(matches!(Market::from_json("{"), Err(Error::Json(_))));
+    }
+
+    #[test]
+    fn test_open_calendars() {
+        let market = make_market();
+        assert_eq!(
+            market.open_calendars(Calendar::from_ymd(2022, 3, 3)),
+            vec!["open".to_string()]
+        );
+        assert_eq!(
+            market.open_calendars(Calendar::from_ymd(2022, 3, 4)),
+            vec!["closed".to_string(), "open".to_string()]
+        );
+        assert!(market
+            .open_calendars(Calendar::from_ymd(2022, 3, 5))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_add_calendar_with_weekend() {
+        let mut market = Market::new();
+        market
+            .add_calendar_with_weekend(
+                "us",
+                &[Weekday::Sat, Weekday::Sun],
+                vec![],
+                Some(2024),
+                Some(2024),
+            )
+            .unwrap()
+            .add_calendar_with_weekend(
+                "gulf",
+                &[Weekday::Fri, Weekday::Sat],
+                vec![],
+                Some(2024),
+                Some(2024),
+            )
+            .unwrap();
+        let friday = Calendar::from_ymd(2024, 3, 8);
+        let sunday = Calendar::from_ymd(2024, 3, 10);
+        assert_eq!(market.open_calendars(friday), vec!["us".to_string()]);
+        assert_eq!(market.open_calendars(sunday), vec!["gulf".to_string()]);
+        assert_eq!(market.all_open(&["us", "gulf"], friday), Some(false));
+    }
+
+    #[test]
+    fn test_status_per_calendar() {
+        let mut market = Market::new();
+        market
+            .add_calendar(
+                "equities",
+                UsExchangeCalendar::with_default_range(false)
+                    .build_calendar(Some(2025), Some(2025)),
+            )
+            .add_calendar(
+                "bonds",
+                UsExchangeCalendar::sifma(false).build_calendar(Some(2025), Some(2025)),
+            );
+        // Columbus Day is a bond market holiday only
+        let columbus_day = Calendar::from_ymd(2025, 10, 13);
+        assert_eq!(
+            market.status("equities", columbus_day),
+            Some(HolidayStatus::BusinessDay)
+        );
+        assert_eq!(
+            market.status("bonds", columbus_day),
+            Some(HolidayStatus::Holiday)
+        );
+        assert_eq!(market.status("fx", columbus_day), None);
+        // bonds close early the day before Good Friday
+        let statuses = market.statuses(Calendar::from_ymd(2025, 4, 17));
+        assert_eq!(statuses["equities"], HolidayStatus::BusinessDay);
+        assert_eq!(statuses["bonds"], HolidayStatus::HalfDay);
+        assert_eq!(statuses.len(), 2);
+    }
+}
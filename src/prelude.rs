@@ -0,0 +1,9 @@
+//! Commonly used types, `use usec::prelude::*;` covers typical usage of the crate.
+
+pub use crate::calendar::{
+    Bounds, BusinessDayFilter, Calendar, CalendarOverride, ClosureReason, HalfCheck, Holiday,
+    HolidayStatus, LazyCalendar, NthWeek, Observance, RangeBreakdown, RollConvention, Session,
+    TradingCalendar, UsExchangeCalendar,
+};
+pub use crate::market::Market;
+pub use chrono::{NaiveDate, Weekday};
@@ -0,0 +1,33 @@
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+use usec::prelude::*;
+
+/// Full-day NYSE closures from 2000 to 2030, including the special closures
+fn reference_holidays() -> BTreeMap<NaiveDate, String> {
+    let csv = include_str!("nyse_holidays_2000_2030.csv");
+    csv.lines()
+        .skip(1)
+        .map(|line| {
+            let (date, name) = line.split_once(',').unwrap();
+            (date.parse().unwrap(), name.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn test_nyse_rules_match_reference() {
+    let mut uscal = UsExchangeCalendar::nyse(false);
+    uscal.populate_cal(Some(2000), Some(2030));
+    let cal = uscal.get_cal();
+    let reference = reference_holidays();
+    let start = Calendar::from_ymd(2000, 1, 1);
+    let end = Calendar::from_ymd(2030, 12, 31);
+    for date in start.iter_days().take_while(|date| *date <= end) {
+        if let Some(name) = reference.get(&date) {
+            assert!(cal.is_holiday(date), "{} ({}) is not a holiday", date, name);
+        } else if date.weekday().num_days_from_monday() < 5 {
+            assert!(cal.is_business_day(date), "{} is not a business day", date);
+        }
+    }
+}